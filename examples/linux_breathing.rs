@@ -1,12 +1,11 @@
-use linux_embedded_hal::I2cdev;
+use linux_embedded_hal::{Delay, I2cdev};
 
 use aw2013::{Aw2013, Current, Led, Timing};
 
 fn main() {
     let i2c = I2cdev::new("/dev/i2c-1").unwrap();
     let mut aw2013 = Aw2013::from_default_address(i2c, [Current::Five; 3]);
-    aw2013.reset().unwrap();
-    aw2013.enable().unwrap();
+    aw2013.init_with_delay(&mut Delay).unwrap();
     aw2013
         .set_breathing(
             Led::Led0,
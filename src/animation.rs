@@ -0,0 +1,155 @@
+//! Non-blocking playback of a sequence of effects.
+
+use embedded_hal::i2c::I2c;
+
+use crate::{Aw2013, Error, FadeTime, Timing};
+
+/// An effect which can be queued on an [`AnimationQueue`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Effect {
+    /// Static RGB value, see [`Aw2013::set_static_rgb`].
+    Static {
+        rgb: [u8; 3],
+        fade_in: Option<FadeTime>,
+        fade_out: Option<FadeTime>,
+    },
+
+    /// Breathing cycle RGB value, see [`Aw2013::set_breathing_rgb`].
+    Breathing { rgb: [u8; 3], timing: Timing },
+}
+
+#[derive(Copy, Clone)]
+struct QueuedEffect {
+    effect: Effect,
+    duration_ms: u32,
+}
+
+/// Fixed-capacity queue of effects which are played one after another.
+///
+/// Effects are added with [`AnimationQueue::push`] and played by regularly calling
+/// [`AnimationQueue::poll`] from your main loop. Each effect is applied once and stays active for its
+/// duration, after which the next effect is applied. The last effect stays on the LEDs after the
+/// queue ran empty.
+///
+/// The queue holds up to `N` effects, including the one currently playing, and does not allocate.
+pub struct AnimationQueue<const N: usize> {
+    effects: [Option<QueuedEffect>; N],
+    head: usize,
+    len: usize,
+    started_at: Option<u32>,
+}
+
+impl<const N: usize> AnimationQueue<N> {
+    /// Create a new empty queue.
+    pub const fn new() -> Self {
+        Self {
+            effects: [None; N],
+            head: 0,
+            len: 0,
+            started_at: None,
+        }
+    }
+
+    /// Add an effect to the end of the queue, to be played for `duration_ms` milliseconds.
+    ///
+    /// If the queue is full, the effect is rejected and handed back in the error.
+    pub fn push(&mut self, effect: Effect, duration_ms: u32) -> Result<(), Effect> {
+        if self.len == N {
+            return Err(effect);
+        }
+
+        self.effects[(self.head + self.len) % N] = Some(QueuedEffect {
+            effect,
+            duration_ms,
+        });
+        self.len += 1;
+
+        Ok(())
+    }
+
+    /// Remove all effects from the queue.
+    ///
+    /// This does not touch the LEDs, the currently playing effect stays active.
+    pub fn clear(&mut self) {
+        self.effects = [None; N];
+        self.head = 0;
+        self.len = 0;
+        self.started_at = None;
+    }
+
+    /// Number of effects in the queue, including the one currently playing.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether the queue is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Whether the queue is full and will reject further effects.
+    pub fn is_full(&self) -> bool {
+        self.len == N
+    }
+
+    /// Advance the queue to the given point in time.
+    ///
+    /// `now_ms` is a monotonic timestamp in milliseconds, which is allowed to wrap around. When the
+    /// current effect has run for its duration, it is removed and the next effect is applied. If
+    /// applying an effect fails, it stays at the front of the queue and is retried on the next poll.
+    pub fn poll<I>(&mut self, aw2013: &mut Aw2013<I>, now_ms: u32) -> Result<(), Error<I::Error>>
+    where
+        I: I2c,
+    {
+        let Some(current) = self.front() else {
+            return Ok(());
+        };
+
+        if let Some(started_at) = self.started_at {
+            if now_ms.wrapping_sub(started_at) < current.duration_ms {
+                return Ok(());
+            }
+
+            self.pop();
+        }
+
+        let Some(next) = self.front() else {
+            return Ok(());
+        };
+
+        match next.effect {
+            Effect::Static {
+                rgb,
+                fade_in,
+                fade_out,
+            } => aw2013.set_static_rgb(rgb, fade_in, fade_out)?,
+            Effect::Breathing { rgb, timing } => aw2013.set_breathing_rgb(rgb, &timing)?,
+        }
+
+        self.started_at = Some(now_ms);
+
+        Ok(())
+    }
+
+    fn front(&self) -> Option<QueuedEffect> {
+        if self.len == 0 {
+            return None;
+        }
+
+        self.effects[self.head]
+    }
+
+    fn pop(&mut self) {
+        self.effects[self.head] = None;
+        self.head = (self.head + 1) % N;
+        self.len -= 1;
+        self.started_at = None;
+    }
+}
+
+impl<const N: usize> Default for AnimationQueue<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
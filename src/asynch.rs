@@ -0,0 +1,382 @@
+//! Asynchronous variant of the driver, built on `embedded-hal-async`.
+
+use embedded_hal_async::i2c::I2c;
+
+use crate::registers::{
+    LED_BREATHE_MODE_MASK, LED_FADE_IN_MASK, LED_FADE_OUT_MASK, LED_RESET_MASK, REG_GLOBAL_CONTROL,
+    REG_LED_ENABLE, REG_LED_MODE_BASE, REG_LED_PWM_BASE, REG_RESET, REG_TIMING_0_BASE,
+    REG_TIMING_1_BASE, REG_TIMING_2_BASE,
+};
+use crate::{
+    assert_valid_address, Address, Current, Error, FadeTime, GlobalConfig, Led, Timing,
+    DEFAULT_ADDRESS, REGISTER_COUNT,
+};
+
+/// Asynchronous AW2013 driver.
+///
+/// This mirrors the core API of [`crate::Aw2013`] with every bus access being awaited, so the bus can
+/// be shared with other tasks without blocking the executor.
+pub struct Aw2013Async<I>
+where
+    I: I2c,
+{
+    i2c: I,
+    address: u8,
+    max_currents: [Current; 3],
+    verify_writes: bool,
+}
+
+impl<I> Aw2013Async<I>
+where
+    I: I2c,
+{
+    /// Create a new AW2013 driver from a pre-configured i2c interface.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the address does not fit into 7 bits.
+    pub fn new(i2c: I, address: u8, max_currents: [Current; 3]) -> Self {
+        assert_valid_address(address);
+
+        Self {
+            i2c,
+            address,
+            max_currents,
+            verify_writes: false,
+        }
+    }
+
+    /// Create a new AW2013 driver from one of the addresses selectable via the AD pin.
+    pub fn from_address(i2c: I, address: Address, max_currents: [Current; 3]) -> Self {
+        Aw2013Async::new(i2c, address.into(), max_currents)
+    }
+
+    /// Create a new AW2013 driver from the default address.
+    pub fn from_default_address(i2c: I, max_currents: [Current; 3]) -> Self {
+        Aw2013Async::new(i2c, DEFAULT_ADDRESS, max_currents)
+    }
+
+    /// Consume the driver and return the underlying i2c interface.
+    pub fn release(self) -> I {
+        self.i2c
+    }
+
+    /// Talk to the controller at a different address from now on.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the address does not fit into 7 bits.
+    pub fn set_address(&mut self, address: u8) {
+        assert_valid_address(address);
+        self.address = address;
+    }
+
+    /// Enable or disable verification of register writes.
+    ///
+    /// See [`crate::Aw2013::set_verify_writes`] for details.
+    pub fn set_verify_writes(&mut self, verify_writes: bool) {
+        self.verify_writes = verify_writes;
+    }
+
+    /// Reset the controller to its default state.
+    ///
+    /// Remember to enable the controller again after the reset if you plan to use it further.
+    pub async fn reset(&mut self) -> Result<(), Error<I::Error>> {
+        self.write_register(REG_RESET, LED_RESET_MASK).await
+    }
+
+    /// Enable the LED controller.
+    pub async fn enable(&mut self) -> Result<(), Error<I::Error>> {
+        self.enable_with_config(GlobalConfig {
+            enable: true,
+            ..GlobalConfig::default()
+        })
+        .await
+    }
+
+    /// Write a full configuration to the global control register.
+    pub async fn enable_with_config(
+        &mut self,
+        config: GlobalConfig,
+    ) -> Result<(), Error<I::Error>> {
+        self.write_register(REG_GLOBAL_CONTROL, config.to_register())
+            .await
+    }
+
+    /// Disable the LED controller.
+    pub async fn disable(&mut self) -> Result<(), Error<I::Error>> {
+        self.write_register(REG_GLOBAL_CONTROL, 0).await
+    }
+
+    /// Turn off all LEDs at once.
+    ///
+    /// See [`crate::Aw2013::all_off`] for details.
+    pub async fn all_off(&mut self) -> Result<(), Error<I::Error>> {
+        self.write_register(REG_LED_ENABLE, 0x0).await
+    }
+
+    /// Set a static RGB value for all LEDs.
+    ///
+    /// You can optionally define fade-in and fade-out effects to fade to or from other values.
+    ///
+    /// Unlike [`Aw2013Async::set_static`], a brightness of zero does not disable the LED. All LEDs stay
+    /// enabled with their PWM set to zero instead, so transitions through black don't toggle the
+    /// enable register and cause flicker. Use [`Aw2013Async::all_off`] to actually turn them off.
+    ///
+    /// Just like with [`crate::Aw2013::set_static_rgb`], all channels start their fades together.
+    pub async fn set_static_rgb(
+        &mut self,
+        rgb: [u8; 3],
+        fade_in: Option<FadeTime>,
+        fade_out: Option<FadeTime>,
+    ) -> Result<(), Error<I::Error>> {
+        let mut modes = [0; 3];
+
+        for led in Led::ALL {
+            modes[led as usize] = self.configure_fades(led, fade_in, fade_out).await?;
+        }
+
+        self.write_registers(REG_LED_MODE_BASE, &modes).await?;
+        self.write_registers(REG_LED_PWM_BASE, &rgb).await?;
+        self.write_register(REG_LED_ENABLE, 0x7).await
+    }
+
+    /// Set a static value for a single LED.
+    ///
+    /// You can optionally define fade-in and fade-out effects to fade to or from other values.
+    /// The fade-in time is stored in the rise period and the fade-out time in the fall period of the
+    /// LED's timing registers. The other fields of these registers are preserved.
+    pub async fn set_static(
+        &mut self,
+        led: Led,
+        brightness: u8,
+        fade_in: Option<FadeTime>,
+        fade_out: Option<FadeTime>,
+    ) -> Result<(), Error<I::Error>> {
+        if brightness == 0 {
+            return self.disable_led(led).await;
+        }
+
+        self.configure_static(led, brightness, fade_in, fade_out)
+            .await?;
+        self.enable_led(led).await?;
+
+        Ok(())
+    }
+
+    /// Set a breathing cycle RGB value for all LEDs.
+    pub async fn set_breathing_rgb(
+        &mut self,
+        rgb: [u8; 3],
+        timing: &Timing,
+    ) -> Result<(), Error<I::Error>> {
+        self.write_register(REG_LED_ENABLE, 0x0).await?;
+
+        for led in Led::ALL {
+            self.write_register(
+                REG_LED_MODE_BASE + (led as u8),
+                self.max_currents[led as usize] as u8,
+            )
+            .await?;
+        }
+
+        for led in Led::ALL {
+            self.write_register(REG_LED_PWM_BASE + (led as u8), rgb[led as usize])
+                .await?;
+            self.configure_timing(led, timing).await?;
+        }
+
+        for led in Led::ALL {
+            self.write_register(
+                REG_LED_MODE_BASE + (led as u8),
+                self.max_currents[led as usize] as u8 | LED_BREATHE_MODE_MASK,
+            )
+            .await?;
+        }
+
+        let mut active_leds = 0;
+
+        for (i, value) in rgb.iter().enumerate() {
+            if *value > 0 {
+                active_leds |= 1 << i;
+            }
+        }
+
+        self.write_register(REG_LED_ENABLE, active_leds).await?;
+
+        Ok(())
+    }
+
+    /// Set a breathing cycle value for a single LED.
+    pub async fn set_breathing(
+        &mut self,
+        led: Led,
+        brightness: u8,
+        timing: &Timing,
+    ) -> Result<(), Error<I::Error>> {
+        self.disable_led(led).await?;
+
+        if brightness == 0 {
+            return Ok(());
+        }
+
+        self.write_register(REG_LED_PWM_BASE + (led as u8), brightness)
+            .await?;
+        self.configure_timing(led, timing).await?;
+        self.write_register(
+            REG_LED_MODE_BASE + (led as u8),
+            self.max_currents[led as usize] as u8 | LED_BREATHE_MODE_MASK,
+        )
+        .await?;
+
+        self.enable_led(led).await?;
+
+        Ok(())
+    }
+
+    /// Write the fade, mode and PWM registers of a static LED, without enabling it.
+    async fn configure_static(
+        &mut self,
+        led: Led,
+        brightness: u8,
+        fade_in: Option<FadeTime>,
+        fade_out: Option<FadeTime>,
+    ) -> Result<(), Error<I::Error>> {
+        let config = self.configure_fades(led, fade_in, fade_out).await?;
+        self.write_register(REG_LED_MODE_BASE + (led as u8), config)
+            .await?;
+        self.write_register(REG_LED_PWM_BASE + (led as u8), brightness)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn configure_fades(
+        &mut self,
+        led: Led,
+        fade_in: Option<FadeTime>,
+        fade_out: Option<FadeTime>,
+    ) -> Result<u8, Error<I::Error>> {
+        let mut config: u8 = self.max_currents[led as usize] as u8;
+
+        if let Some(fade_in) = fade_in {
+            config |= LED_FADE_IN_MASK;
+            self.modify_register(
+                REG_TIMING_0_BASE + (led as u8) * 3,
+                0xf0,
+                (fade_in as u8) << 4,
+            )
+            .await?;
+        }
+
+        if let Some(fade_out) = fade_out {
+            config |= LED_FADE_OUT_MASK;
+            self.modify_register(
+                REG_TIMING_1_BASE + (led as u8) * 3,
+                0xf0,
+                (fade_out as u8) << 4,
+            )
+            .await?;
+        }
+
+        Ok(config)
+    }
+
+    async fn configure_timing(&mut self, led: Led, timing: &Timing) -> Result<(), Error<I::Error>> {
+        let [timing_0, timing_1, timing_2] = timing.to_registers();
+        self.write_register(REG_TIMING_0_BASE + (led as u8) * 3, timing_0)
+            .await?;
+        self.write_register(REG_TIMING_1_BASE + (led as u8) * 3, timing_1)
+            .await?;
+        self.write_register(REG_TIMING_2_BASE + (led as u8) * 3, timing_2)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn disable_led(&mut self, led: Led) -> Result<(), Error<I::Error>> {
+        let enable_value = self.read_register(REG_LED_ENABLE).await?;
+        self.write_register(REG_LED_ENABLE, enable_value & (!(1 << (led as u8))))
+            .await
+    }
+
+    async fn enable_led(&mut self, led: Led) -> Result<(), Error<I::Error>> {
+        let enable_value = self.read_register(REG_LED_ENABLE).await?;
+        self.write_register(REG_LED_ENABLE, enable_value | (1 << (led as u8)))
+            .await
+    }
+
+    async fn modify_register(
+        &mut self,
+        address: u8,
+        mask: u8,
+        value: u8,
+    ) -> Result<(), Error<I::Error>> {
+        let current = self.read_register(address).await?;
+        self.write_register(address, current & !mask | value & mask)
+            .await
+    }
+
+    async fn write_register(&mut self, address: u8, data: u8) -> Result<(), Error<I::Error>> {
+        self.i2c
+            .write(self.address, &[address, data])
+            .await
+            .map_err(Error::I2c)?;
+
+        if !self.verify_writes || address == REG_RESET {
+            return Ok(());
+        }
+
+        let got = self.read_register(address).await?;
+
+        if got != data {
+            return Err(Error::VerifyMismatch {
+                reg: address,
+                expected: data,
+                got,
+            });
+        }
+
+        Ok(())
+    }
+
+    async fn write_registers(&mut self, address: u8, data: &[u8]) -> Result<(), Error<I::Error>> {
+        let len = data.len();
+        let mut buffer = [0; REGISTER_COUNT + 1];
+        buffer[0] = address;
+        buffer[1..=len].copy_from_slice(data);
+
+        self.i2c
+            .write(self.address, &buffer[..=len])
+            .await
+            .map_err(Error::I2c)?;
+
+        if !self.verify_writes {
+            return Ok(());
+        }
+
+        for (i, expected) in data.iter().enumerate() {
+            let reg = address + i as u8;
+            let got = self.read_register(reg).await?;
+
+            if got != *expected {
+                return Err(Error::VerifyMismatch {
+                    reg,
+                    expected: *expected,
+                    got,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn read_register(&mut self, address: u8) -> Result<u8, Error<I::Error>> {
+        let mut buffer: [u8; 1] = [0];
+        self.i2c
+            .write_read(self.address, &[address], &mut buffer)
+            .await
+            .map_err(Error::I2c)?;
+        Ok(buffer[0])
+    }
+}
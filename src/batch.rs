@@ -0,0 +1,111 @@
+//! Deferred register writes which are coalesced into burst transactions.
+
+use embedded_hal::i2c::I2c;
+
+use crate::registers::{REG_LED_ENABLE, REG_LED_MODE_BASE, REG_LED_PWM_BASE, REG_TIMING_0_BASE};
+use crate::{Aw2013, Error, Led, Timing, REGISTER_COUNT};
+
+/// A batch of register writes, created through [`Aw2013::begin_batch`].
+///
+/// Writes are only recorded and nothing hits the bus until [`Batch::flush`] is called. Dropping the
+/// batch without flushing discards all recorded writes.
+///
+/// When flushing, writes are applied in ascending register order instead of the order they were
+/// recorded in, and contiguous registers are combined into a single auto-incrementing transaction.
+/// If a register is written more than once, only the last value is sent. A pending write of the LED
+/// enable register is always sent last in its own transaction, so channels are only switched on once
+/// all of their other registers are in place.
+pub struct Batch<'a, I>
+where
+    I: I2c,
+{
+    aw2013: &'a mut Aw2013<I>,
+    pending: [Option<u8>; REGISTER_COUNT],
+}
+
+impl<'a, I> Batch<'a, I>
+where
+    I: I2c,
+{
+    pub(crate) fn new(aw2013: &'a mut Aw2013<I>) -> Self {
+        Self {
+            aw2013,
+            pending: [None; REGISTER_COUNT],
+        }
+    }
+
+    /// Record a write of a raw value to a register.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the register lies outside the LED register map (`0x00` to `0x3f`).
+    pub fn write_register(&mut self, register: u8, value: u8) -> &mut Self {
+        assert!(
+            (register as usize) < REGISTER_COUNT,
+            "register {:#04x} is outside the LED register map",
+            register
+        );
+
+        self.pending[register as usize] = Some(value);
+        self
+    }
+
+    /// Record a write of the LED enable register, with bit `n` enabling LED `n`.
+    pub fn set_enabled_leds(&mut self, mask: u8) -> &mut Self {
+        self.write_register(REG_LED_ENABLE, mask & 0x7)
+    }
+
+    /// Record a write of the mode register of a single LED.
+    pub fn set_mode(&mut self, led: Led, mode: u8) -> &mut Self {
+        self.write_register(REG_LED_MODE_BASE + (led as u8), mode)
+    }
+
+    /// Record a write of the PWM register of a single LED.
+    pub fn set_pwm(&mut self, led: Led, brightness: u8) -> &mut Self {
+        self.write_register(REG_LED_PWM_BASE + (led as u8), brightness)
+    }
+
+    /// Record a write of the timing registers of a single LED.
+    ///
+    /// Values which are set too high are clamped, just like with [`Aw2013::set_breathing`].
+    pub fn set_timing(&mut self, led: Led, timing: &Timing) -> &mut Self {
+        for (i, value) in timing.to_registers().into_iter().enumerate() {
+            self.write_register(REG_TIMING_0_BASE + (led as u8) * 3 + i as u8, value);
+        }
+
+        self
+    }
+
+    /// Write all recorded registers to the bus.
+    ///
+    /// Every run of contiguous registers is sent as a single transaction, followed by the LED enable
+    /// register if it was written.
+    pub fn flush(mut self) -> Result<(), Error<I::Error>> {
+        let enable_value = self.pending[REG_LED_ENABLE as usize].take();
+        let mut buffer = [0; REGISTER_COUNT];
+        let mut register = 0;
+
+        while register < REGISTER_COUNT {
+            if self.pending[register].is_none() {
+                register += 1;
+                continue;
+            }
+
+            let start = register;
+
+            while let Some(Some(value)) = self.pending.get(register) {
+                buffer[register - start] = *value;
+                register += 1;
+            }
+
+            self.aw2013
+                .write_registers(start as u8, &buffer[..register - start])?;
+        }
+
+        if let Some(value) = enable_value {
+            self.aw2013.write_register(REG_LED_ENABLE, value)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,157 @@
+//! Builder for configuring and initializing the driver in one expression.
+
+use embedded_hal::delay::DelayNs;
+use embedded_hal::i2c::I2c;
+
+use crate::{Address, Aw2013, Current, Error, Led, DEFAULT_ADDRESS};
+
+/// Builder for an [`Aw2013`] driver.
+///
+/// Defaults to [`Address::Default`] and [`Current::Five`] on all LEDs, with neither probe, reset nor
+/// enable being performed on build.
+pub struct Aw2013Builder {
+    address: u8,
+    max_currents: [Current; 3],
+    probe: bool,
+    reset: bool,
+    enable: bool,
+    verify_writes: bool,
+    channel_map: [Led; 3],
+}
+
+impl Aw2013Builder {
+    /// Create a new builder with the default configuration.
+    pub const fn new() -> Self {
+        Self {
+            address: DEFAULT_ADDRESS,
+            max_currents: [Current::Five; 3],
+            probe: false,
+            reset: false,
+            enable: false,
+            verify_writes: false,
+            channel_map: Led::ALL,
+        }
+    }
+
+    /// Set the I2C address of the controller to one of the addresses selectable via the AD pin.
+    pub const fn address(mut self, address: Address) -> Self {
+        self.address = address as u8;
+        self
+    }
+
+    /// Set the max current of a single LED.
+    pub const fn max_current(mut self, led: Led, current: Current) -> Self {
+        self.max_currents[led as usize] = current;
+        self
+    }
+
+    /// Set the max currents of all LEDs.
+    pub const fn max_currents(mut self, max_currents: [Current; 3]) -> Self {
+        self.max_currents = max_currents;
+        self
+    }
+
+    /// Check for a responding controller before anything else when building the driver.
+    ///
+    /// If no AW2013 responds, building fails with [`Error::DeviceNotFound`]. See [`Aw2013::probe`]
+    /// for how the check is performed.
+    pub const fn probe(mut self, probe: bool) -> Self {
+        self.probe = probe;
+        self
+    }
+
+    /// Reset the controller when building the driver.
+    ///
+    /// The chip needs a short settling time after the reset. Only [`Aw2013Builder::build_with_delay`]
+    /// waits for it. With [`Aw2013Builder::build`], a following enable may be ignored.
+    pub const fn reset(mut self, reset: bool) -> Self {
+        self.reset = reset;
+        self
+    }
+
+    /// Enable the controller when building the driver.
+    ///
+    /// When combined with [`Aw2013Builder::reset`], the controller is enabled after the reset. Use
+    /// [`Aw2013Builder::build_with_delay`] in that case, so the chip has settled before the enable.
+    pub const fn enable(mut self, enable: bool) -> Self {
+        self.enable = enable;
+        self
+    }
+
+    /// Verify all register writes, see [`Aw2013::set_verify_writes`].
+    pub const fn verify_writes(mut self, verify_writes: bool) -> Self {
+        self.verify_writes = verify_writes;
+        self
+    }
+
+    /// Configure which LED drives which color component, see [`Aw2013::set_channel_map`].
+    pub const fn channel_map(mut self, channel_map: [Led; 3]) -> Self {
+        self.channel_map = channel_map;
+        self
+    }
+
+    /// Build the driver, performing the probe, reset and enable sequence if requested.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the channel map does not contain every LED exactly once.
+    pub fn build<I>(self, i2c: I) -> Result<Aw2013<I>, Error<I::Error>>
+    where
+        I: I2c,
+    {
+        self.build_with(i2c, None)
+    }
+
+    /// Build the driver like [`Aw2013Builder::build`], but wait for the chip to settle after the
+    /// reset, see [`Aw2013::reset_with_delay`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the channel map does not contain every LED exactly once.
+    pub fn build_with_delay<I>(
+        self,
+        i2c: I,
+        delay: &mut impl DelayNs,
+    ) -> Result<Aw2013<I>, Error<I::Error>>
+    where
+        I: I2c,
+    {
+        self.build_with(i2c, Some(delay))
+    }
+
+    fn build_with<I>(
+        self,
+        i2c: I,
+        delay: Option<&mut dyn DelayNs>,
+    ) -> Result<Aw2013<I>, Error<I::Error>>
+    where
+        I: I2c,
+    {
+        let mut aw2013 = Aw2013::new(i2c, self.address, self.max_currents);
+        aw2013.set_verify_writes(self.verify_writes);
+        aw2013.set_channel_map(self.channel_map);
+
+        if self.probe && !aw2013.probe()? {
+            return Err(Error::DeviceNotFound);
+        }
+
+        if self.reset {
+            match delay {
+                Some(mut delay) => aw2013.reset_with_delay(&mut delay)?,
+                None => aw2013.reset()?,
+            }
+        }
+
+        if self.enable {
+            aw2013.enable()?;
+        }
+
+        Ok(aw2013)
+    }
+}
+
+impl Default for Aw2013Builder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
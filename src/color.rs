@@ -0,0 +1,157 @@
+//! Color types and conversions.
+
+// Gamma correction lookup table for a gamma of 2.2
+#[rustfmt::skip]
+const GAMMA_TABLE: [u8; 256] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1,
+    1, 1, 1, 1, 1, 1, 1, 1, 1, 2, 2, 2, 2, 2, 2, 2,
+    3, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 6, 6, 6,
+    6, 7, 7, 7, 8, 8, 8, 9, 9, 9, 10, 10, 11, 11, 11, 12,
+    12, 13, 13, 13, 14, 14, 15, 15, 16, 16, 17, 17, 18, 18, 19, 19,
+    20, 20, 21, 22, 22, 23, 23, 24, 25, 25, 26, 26, 27, 28, 28, 29,
+    30, 30, 31, 32, 33, 33, 34, 35, 35, 36, 37, 38, 39, 39, 40, 41,
+    42, 43, 43, 44, 45, 46, 47, 48, 49, 49, 50, 51, 52, 53, 54, 55,
+    56, 57, 58, 59, 60, 61, 62, 63, 64, 65, 66, 67, 68, 69, 70, 71,
+    73, 74, 75, 76, 77, 78, 79, 81, 82, 83, 84, 85, 87, 88, 89, 90,
+    91, 93, 94, 95, 97, 98, 99, 100, 102, 103, 105, 106, 107, 109, 110, 111,
+    113, 114, 116, 117, 119, 120, 121, 123, 124, 126, 127, 129, 130, 132, 133, 135,
+    137, 138, 140, 141, 143, 145, 146, 148, 149, 151, 153, 154, 156, 158, 159, 161,
+    163, 165, 166, 168, 170, 172, 173, 175, 177, 179, 181, 182, 184, 186, 188, 190,
+    192, 194, 196, 197, 199, 201, 203, 205, 207, 209, 211, 213, 215, 217, 219, 221,
+    223, 225, 227, 229, 231, 234, 236, 238, 240, 242, 244, 246, 248, 251, 253, 255,
+];
+
+/// An RGB color value.
+///
+/// Converts from and into the `[u8; 3]` arrays accepted by the RGB methods of [`crate::Aw2013`].
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Rgb {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Rgb {
+    /// Create a new color from its components.
+    pub const fn new(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b }
+    }
+
+    /// Convert an 8-bit HSV triple to RGB.
+    ///
+    /// The hue covers the full color wheel over `0..=255`, so it wraps around from `255` back to red
+    /// at `0`. A saturation of zero results in a gray with all components equal to the value.
+    ///
+    /// ```
+    /// use aw2013::Rgb;
+    ///
+    /// assert_eq!(Rgb::from_hsv([0, 255, 255]), Rgb::new(255, 0, 0));
+    /// assert_eq!(Rgb::from_hsv([85, 255, 255]), Rgb::new(1, 255, 0));
+    /// assert_eq!(Rgb::from_hsv([123, 0, 77]), Rgb::new(77, 77, 77));
+    /// ```
+    pub fn from_hsv(hsv: [u8; 3]) -> Rgb {
+        let [hue, saturation, value] = hsv;
+
+        if saturation == 0 {
+            return Rgb::new(value, value, value);
+        }
+
+        let sector = (hue as u16 * 6) >> 8;
+        let fraction = (hue as u16 * 6) & 0xff;
+        let saturation = saturation as u16;
+        let value = value as u16;
+
+        let p = (value * (255 - saturation) / 255) as u8;
+        let q = (value * (255 - saturation * fraction / 255) / 255) as u8;
+        let t = (value * (255 - saturation * (255 - fraction) / 255) / 255) as u8;
+        let v = value as u8;
+
+        match sector {
+            0 => Rgb::new(v, t, p),
+            1 => Rgb::new(q, v, p),
+            2 => Rgb::new(p, v, t),
+            3 => Rgb::new(p, q, v),
+            4 => Rgb::new(t, p, v),
+            _ => Rgb::new(v, p, q),
+        }
+    }
+
+    /// Parse a color from a hex string.
+    ///
+    /// Accepts six digits (`"FF8800"`) or the three digit shorthand (`"F80"`), both with an optional
+    /// leading `#` and in either case. Returns `None` for anything else.
+    ///
+    /// ```
+    /// use aw2013::Rgb;
+    ///
+    /// assert_eq!(Rgb::from_hex("#FF8800"), Some(Rgb::new(0xff, 0x88, 0x00)));
+    /// assert_eq!(Rgb::from_hex("ff8800"), Some(Rgb::new(0xff, 0x88, 0x00)));
+    /// assert_eq!(Rgb::from_hex("#f80"), Some(Rgb::new(0xff, 0x88, 0x00)));
+    /// assert_eq!(Rgb::from_hex("#FF880"), None);
+    /// assert_eq!(Rgb::from_hex("#GG8800"), None);
+    /// assert_eq!(Rgb::from_hex(""), None);
+    /// ```
+    pub fn from_hex(value: &str) -> Option<Rgb> {
+        let digits = value.strip_prefix('#').unwrap_or(value).as_bytes();
+
+        match digits {
+            [r, g, b] => Some(Rgb::new(
+                hex_digit(*r)? * 0x11,
+                hex_digit(*g)? * 0x11,
+                hex_digit(*b)? * 0x11,
+            )),
+            [r1, r2, g1, g2, b1, b2] => Some(Rgb::new(
+                hex_digit(*r1)? << 4 | hex_digit(*r2)?,
+                hex_digit(*g1)? << 4 | hex_digit(*g2)?,
+                hex_digit(*b1)? << 4 | hex_digit(*b2)?,
+            )),
+            _ => None,
+        }
+    }
+}
+
+impl From<[u8; 3]> for Rgb {
+    fn from(rgb: [u8; 3]) -> Self {
+        Rgb::new(rgb[0], rgb[1], rgb[2])
+    }
+}
+
+impl From<Rgb> for [u8; 3] {
+    fn from(rgb: Rgb) -> Self {
+        [rgb.r, rgb.g, rgb.b]
+    }
+}
+
+#[cfg(feature = "rgb")]
+impl From<rgb::RGB8> for Rgb {
+    fn from(rgb: rgb::RGB8) -> Self {
+        Rgb::new(rgb.r, rgb.g, rgb.b)
+    }
+}
+
+#[cfg(feature = "rgb")]
+impl From<Rgb> for rgb::RGB8 {
+    fn from(rgb: Rgb) -> Self {
+        rgb::RGB8::new(rgb.r, rgb.g, rgb.b)
+    }
+}
+
+fn hex_digit(digit: u8) -> Option<u8> {
+    match digit {
+        b'0'..=b'9' => Some(digit - b'0'),
+        b'a'..=b'f' => Some(digit - b'a' + 10),
+        b'A'..=b'F' => Some(digit - b'A' + 10),
+        _ => None,
+    }
+}
+
+/// Map a perceptual brightness value to a PWM value.
+///
+/// The eye perceives brightness non-linearly, so linear steps in PWM look uneven: low values change
+/// dramatically while high values barely differ. This applies a gamma of 2.2 through a precomputed
+/// lookup table, so that linear steps in `value` result in perceptually even steps.
+pub const fn gamma_correct(value: u8) -> u8 {
+    GAMMA_TABLE[value as usize]
+}
@@ -0,0 +1,58 @@
+//! Errors returned by the driver.
+
+use core::fmt;
+
+use crate::TimingError;
+
+/// Errors which can occur while talking to the controller.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Error<E> {
+    /// The underlying I2C bus reported an error.
+    I2c(E),
+
+    /// A register did not hold the written value when reading it back.
+    ///
+    /// This is only reported when write verification is enabled, see
+    /// [`crate::Aw2013::set_verify_writes`].
+    VerifyMismatch { reg: u8, expected: u8, got: u8 },
+
+    /// A timing passed to one of the checked methods is out of range.
+    InvalidTiming(TimingError),
+
+    /// No AW2013 responded at the configured address, see [`crate::Aw2013::probe`].
+    DeviceNotFound,
+
+    /// A breathing effect was requested with a non-zero lower bound, which the controller cannot
+    /// produce, see [`crate::Aw2013::set_breathing_range`].
+    UnsupportedBreathingFloor(u8),
+}
+
+impl<E> From<TimingError> for Error<E> {
+    fn from(error: TimingError) -> Self {
+        Error::InvalidTiming(error)
+    }
+}
+
+impl<E> fmt::Display for Error<E>
+where
+    E: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::I2c(error) => write!(f, "I2C error: {:?}", error),
+            Error::VerifyMismatch { reg, expected, got } => write!(
+                f,
+                "register {:#04x} holds {:#04x} after writing {:#04x}",
+                reg, got, expected
+            ),
+            Error::InvalidTiming(error) => write!(f, "invalid timing: {}", error),
+            Error::DeviceNotFound => write!(f, "no AW2013 found at the configured address"),
+            Error::UnsupportedBreathingFloor(min) => write!(
+                f,
+                "breathing cannot start at a brightness of {}, only zero is supported",
+                min
+            ),
+        }
+    }
+}
@@ -0,0 +1,68 @@
+//! Coordinated control of multiple controllers.
+
+use embedded_hal::i2c::I2c;
+
+use crate::registers::REG_LED_ENABLE;
+use crate::{Aw2013, Error, Timing};
+
+/// A group of controllers which are driven together, e.g. on a board with more than three LEDs.
+///
+/// Effects are applied in two phases: First every controller is fully configured while its LEDs
+/// stay disabled, then the enable registers of all controllers are written back to back. This keeps
+/// the phase offset between the controllers as small as the bus allows. With write verification
+/// enabled, the enable registers are only read back once all of them were written.
+pub struct Aw2013Group<'a, I>
+where
+    I: I2c,
+{
+    chips: &'a mut [Aw2013<I>],
+}
+
+impl<'a, I> Aw2013Group<'a, I>
+where
+    I: I2c,
+{
+    /// Create a new group from a slice of drivers.
+    pub fn new(chips: &'a mut [Aw2013<I>]) -> Self {
+        Self { chips }
+    }
+
+    /// Access the individual drivers of the group.
+    pub fn chips(&mut self) -> &mut [Aw2013<I>] {
+        self.chips
+    }
+
+    /// Start a synchronized breathing cycle with the same RGB value on all controllers.
+    ///
+    /// The channel map of each controller is respected, see [`Aw2013::set_channel_map`].
+    pub fn set_breathing_rgb(
+        &mut self,
+        rgb: [u8; 3],
+        timing: &Timing,
+    ) -> Result<(), Error<I::Error>> {
+        for chip in self.chips.iter_mut() {
+            chip.stage_breathing_rgb(rgb, [timing; 3])?;
+        }
+
+        for chip in self.chips.iter_mut() {
+            let active_leds = chip.breathing_enable_mask(rgb);
+            chip.write_register_unverified(REG_LED_ENABLE, active_leds)?;
+        }
+
+        for chip in self.chips.iter_mut() {
+            let active_leds = chip.breathing_enable_mask(rgb);
+            chip.verify_registers(REG_LED_ENABLE, &[active_leds])?;
+        }
+
+        Ok(())
+    }
+
+    /// Turn off all LEDs on all controllers, see [`Aw2013::all_off`].
+    pub fn all_off(&mut self) -> Result<(), Error<I::Error>> {
+        for chip in self.chips.iter_mut() {
+            chip.all_off()?;
+        }
+
+        Ok(())
+    }
+}
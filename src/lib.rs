@@ -1,30 +1,80 @@
 //! Driver for the AW2013 3-Channel LED Controller
 //!
 //! Provides a simple interface for the AW2013 LED controller.
+//!
+//! With the `defmt` feature enabled, all public types implement `defmt::Format` and can be logged
+//! directly:
+//!
+//! ```ignore
+//! use aw2013::Current;
+//!
+//! defmt::info!("{}", Current::Ten);
+//! ```
 
 #![no_std]
 
-use embedded_hal::i2c::I2c;
-
-// Register addresses
-const REG_RESET: u8 = 0x00;
-const REG_GLOBAL_CONTROL: u8 = 0x01;
-const REG_LED_ENABLE: u8 = 0x30;
-const REG_LED_MODE_BASE: u8 = 0x31;
-const REG_LED_PWM_BASE: u8 = 0x34;
-const REG_TIMING_0_BASE: u8 = 0x37;
-const REG_TIMING_1_BASE: u8 = 0x38;
-const REG_TIMING_2_BASE: u8 = 0x39;
-
-// Register bits
-const LED_MODULE_ENABLE_MASK: u8 = 0x01;
-const LED_FADE_OUT_MASK: u8 = 0x40;
-const LED_FADE_IN_MASK: u8 = 0x20;
-const LED_BREATHE_MODE_MASK: u8 = 0x10;
-const LED_RESET_MASK: u8 = 0x55;
+use core::fmt;
+
+use embedded_hal::delay::DelayNs;
+use embedded_hal::i2c::{Error as _, ErrorKind, I2c};
+
+mod animation;
+#[cfg(feature = "async")]
+mod asynch;
+mod batch;
+mod builder;
+mod color;
+mod error;
+mod group;
+#[cfg(any(test, feature = "test-util"))]
+pub mod mock;
+pub mod registers;
+mod timing;
+
+pub use animation::{AnimationQueue, Effect};
+#[cfg(feature = "async")]
+pub use asynch::Aw2013Async;
+pub use batch::Batch;
+pub use builder::Aw2013Builder;
+pub use color::{gamma_correct, Rgb};
+pub use error::Error;
+pub use group::Aw2013Group;
+pub use timing::{Durations, FadeTime, RoundingInfo, Timing, TimingError};
+
+use registers::*;
+
+// Address of the controller unless configured otherwise
+const DEFAULT_ADDRESS: u8 = Address::Default as u8;
+
+// Number of registers covering the LED register map
+const REGISTER_COUNT: usize = 0x40;
+
+// Blink code timing in milliseconds
+const BLINK_CODE_ON_MS: u32 = 250;
+const BLINK_CODE_OFF_MS: u32 = 250;
+const BLINK_CODE_GAP_MS: u32 = 1000;
+
+// Settling time after a software reset in microseconds
+const RESET_DELAY_US: u32 = 1000;
+
+/// I2C addresses the controller can be strapped to via its AD pin.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(u8)]
+pub enum Address {
+    Default = 0x45,
+    Alternate = 0x44,
+}
+
+impl From<Address> for u8 {
+    fn from(address: Address) -> Self {
+        address as u8
+    }
+}
 
 /// LED mapping for the three different LEDs as defined by the specs.
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[repr(u8)]
 pub enum Led {
     Led0 = 0x0,
@@ -32,8 +82,19 @@ pub enum Led {
     Led2 = 0x2,
 }
 
+impl Led {
+    /// All LEDs in register order.
+    pub const ALL: [Led; 3] = [Led::Led0, Led::Led1, Led::Led2];
+
+    /// Iterate over all LEDs in register order.
+    pub fn iter() -> impl Iterator<Item = Led> {
+        Self::ALL.into_iter()
+    }
+}
+
 /// Current to drive an LED in milliamps.
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[repr(u8)]
 pub enum Current {
     Zero = 0x0,
@@ -42,74 +103,176 @@ pub enum Current {
     Fifteen = 0x3,
 }
 
-/// Timing configuration for breathing effects.
+impl Current {
+    fn from_bits(bits: u8) -> Self {
+        match bits & LED_CURRENT_MASK {
+            0x0 => Current::Zero,
+            0x1 => Current::Five,
+            0x2 => Current::Ten,
+            _ => Current::Fifteen,
+        }
+    }
+
+    /// Current in milliamps.
+    pub const fn milliamps(self) -> u8 {
+        match self {
+            Current::Zero => 0,
+            Current::Five => 5,
+            Current::Ten => 10,
+            Current::Fifteen => 15,
+        }
+    }
+}
+
+/// Error returned when converting a milliamp value which is not supported into a [`Current`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct InvalidCurrent(pub u8);
+
+impl fmt::Display for InvalidCurrent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "unsupported current of {}mA, expected 0, 5, 10 or 15",
+            self.0
+        )
+    }
+}
+
+impl TryFrom<u8> for Current {
+    type Error = InvalidCurrent;
+
+    /// Convert a current in milliamps into a [`Current`].
+    fn try_from(milliamps: u8) -> Result<Self, Self::Error> {
+        match milliamps {
+            0 => Ok(Current::Zero),
+            5 => Ok(Current::Five),
+            10 => Ok(Current::Ten),
+            15 => Ok(Current::Fifteen),
+            _ => Err(InvalidCurrent(milliamps)),
+        }
+    }
+}
+
+/// Configuration of the global control register.
+///
+/// | Bit    | Meaning                                      |
+/// |--------|----------------------------------------------|
+/// | 0      | Chip enable, see [`GlobalConfig::enable`]    |
+/// | 1 to 7 | Not modelled, see [`GlobalConfig::reserved`] |
+///
+/// Bit 0 is the only bit the driver relies on. The AW2013 has an interrupt output pin, and the
+/// breathing completion flags in the status register suggest a matching interrupt enable, but its
+/// location could not be confirmed against the datasheet. Thus no typed field is offered for it, and
+/// bits 1 to 7 are exposed raw for boards which need to set them.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GlobalConfig {
+    /// Bit 0: Enable the chip.
+    ///
+    /// While disabled, the internal oscillator is stopped and all LED outputs are off.
+    pub enable: bool,
+
+    /// Bits 1 to 7: Not modelled by the driver.
+    ///
+    /// These are written to the register verbatim, the value of bit 0 is ignored. Leave this at zero
+    /// unless your board specifically requires otherwise, e.g. to enable the interrupt output.
+    pub reserved: u8,
+}
+
+impl GlobalConfig {
+    fn to_register(self) -> u8 {
+        let mut value = self.reserved & !LED_MODULE_ENABLE_MASK;
+
+        if self.enable {
+            value |= LED_MODULE_ENABLE_MASK;
+        }
+
+        value
+    }
+}
+
+/// Register configuration of a single LED channel.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LedConfig {
+    /// Raw value of the mode register (max current, fade and breathing bits).
+    pub mode: u8,
+
+    /// Raw value of the PWM register.
+    pub pwm: u8,
+
+    /// Raw values of the three timing registers.
+    pub timing: [u8; 3],
+}
+
+/// Full register configuration of the controller.
+///
+/// This can be read from one controller via [`Aw2013::read_config`] and applied to another one via
+/// [`Aw2013::apply_config`], e.g. to mirror the state of one chip to another on a multi-chip board.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Aw2013Config {
+    /// Raw value of the global control register.
+    pub global_control: u8,
+
+    /// Raw value of the LED enable register.
+    pub led_enable: u8,
+
+    /// Configuration of each LED channel, indexed by [`Led`].
+    pub leds: [LedConfig; 3],
+}
+
+/// Snapshot of the LED output state of the controller, including the timing of breathing and fade
+/// effects.
+///
+/// Taken via [`Aw2013::snapshot`] and written back via [`Aw2013::restore`], e.g. to temporarily run
+/// another effect and return to the previous state afterwards.
+///
+/// The [`fmt::Debug`] implementation prints one line per LED with all values in hex.
+#[derive(Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DeviceState {
+    /// Raw value of the LED enable register.
+    pub led_enable: u8,
+
+    /// Raw values of the mode registers, indexed by [`Led`].
+    pub modes: [u8; 3],
+
+    /// Raw values of the PWM registers, indexed by [`Led`].
+    pub pwms: [u8; 3],
+
+    /// Raw values of the three timing registers of each LED, indexed by [`Led`].
+    pub timings: [[u8; 3]; 3],
+}
+
+/// Raw values of all LED related registers, read via [`Aw2013::dump_registers`].
 ///
-/// If a supplied value is set too high, it is automatically clamped to the
-/// allowed range.
-pub struct Timing {
-    /// Delay time before breathing cycle starts.
-    ///
-    /// - 0: 0s
-    /// - 1: 0.13s
-    /// - 2: 0.26s
-    /// - 3: 0.52s
-    /// - 4: 1.04s
-    /// - 5: 2.08s
-    /// - 6: 4.16s
-    /// - 7: 8.32s
-    /// - 8: 16.64s
-    pub delay: u8,
-
-    /// Rise period of breathing cycle.
-    ///
-    /// - 0: 0.13s
-    /// - 1: 0.26s
-    /// - 2: 0.52s
-    /// - 3: 1.04s
-    /// - 4: 2.08s
-    /// - 5: 4.16s
-    /// - 6: 8.32s
-    /// - 7: 16.64s
-    pub rise: u8,
-
-    /// Hold period of the breathing cycle.
-    ///
-    /// - 0: 0.13s
-    /// - 1: 0.26s
-    /// - 2: 0.52s
-    /// - 3: 1.04s
-    /// - 4: 2.08s
-    /// - 5: 4.16s
-    pub hold: u8,
-
-    /// Fall period of breathing cycle.
-    ///
-    /// - 0: 0.13s
-    /// - 1: 0.26s
-    /// - 2: 0.52s
-    /// - 3: 1.04s
-    /// - 4: 2.08s
-    /// - 5: 4.16s
-    /// - 6: 8.32s
-    /// - 7: 16.64s
-    pub fall: u8,
-
-    /// Off period of breathing cycle.
-    ///
-    /// - 0: 0.13s
-    /// - 1: 0.26s
-    /// - 2: 0.52s
-    /// - 3: 1.04s
-    /// - 4: 2.08s
-    /// - 5: 4.16s
-    /// - 6: 8.32s
-    /// - 7: 16.64s
-    pub off: u8,
-
-    /// Number of breathing cycles.
-    ///
-    /// Zero cycles means infinite, maximum configurable cycles is 15.
-    pub cycles: u8,
+/// This is the same as a [`DeviceState`], whose [`fmt::Debug`] implementation prints one line per
+/// LED with all values in hex, which is meant to be pasted into bug reports.
+pub type RegisterDump = DeviceState;
+
+impl fmt::Debug for DeviceState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "DeviceState {{")?;
+        writeln!(f, "    led_enable: {:#05b}", self.led_enable)?;
+
+        for led in Led::ALL {
+            let index = led as usize;
+            let [t0, t1, t2] = self.timings[index];
+            writeln!(
+                f,
+                "    {:?}: mode={:#04x} pwm={:#04x} timing=[{:#04x}, {:#04x}, {:#04x}]",
+                led, self.modes[index], self.pwms[index], t0, t1, t2
+            )?;
+        }
+
+        write!(f, "}}")
+    }
 }
 
 pub struct Aw2013<I>
@@ -119,6 +282,11 @@ where
     i2c: I,
     address: u8,
     max_currents: [Current; 3],
+    verify_writes: bool,
+    pwm_shadow: [Option<u8>; 3],
+    pwm_ceilings: [u8; 3],
+    channel_map: [Led; 3],
+    sleep_config: Option<Aw2013Config>,
 }
 
 impl<I> Aw2013<I>
@@ -126,129 +294,563 @@ where
     I: I2c,
 {
     /// Create a new AW2013 driver from a pre-configured i2c interface.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the address does not fit into 7 bits.
     pub fn new(i2c: I, address: u8, max_currents: [Current; 3]) -> Self {
+        assert_valid_address(address);
+
         Self {
             i2c,
             address,
             max_currents,
+            verify_writes: false,
+            pwm_shadow: [None; 3],
+            pwm_ceilings: [0xff; 3],
+            channel_map: Led::ALL,
+            sleep_config: None,
         }
     }
 
+    /// Create a new AW2013 driver from one of the addresses selectable via the AD pin.
+    pub fn from_address(i2c: I, address: Address, max_currents: [Current; 3]) -> Self {
+        Aw2013::new(i2c, address.into(), max_currents)
+    }
+
     /// Create a new AW2013 driver from the default address.
     pub fn from_default_address(i2c: I, max_currents: [Current; 3]) -> Self {
-        Aw2013::new(i2c, 0x45, max_currents)
+        Aw2013::new(i2c, DEFAULT_ADDRESS, max_currents)
+    }
+
+    /// Create a builder to configure and initialize the driver in one expression.
+    pub const fn builder() -> Aw2013Builder {
+        Aw2013Builder::new()
+    }
+
+    /// Consume the driver and return the underlying i2c interface.
+    pub fn release(self) -> I {
+        self.i2c
+    }
+
+    /// Talk to the controller at a different address from now on.
+    ///
+    /// The bus, max currents and all other settings of the driver are kept. State cached for the
+    /// previous controller, i.e. the last written PWM values and a configuration saved by
+    /// [`Aw2013::sleep`], is discarded.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the address does not fit into 7 bits.
+    pub fn set_address(&mut self, address: u8) {
+        assert_valid_address(address);
+
+        self.address = address;
+        self.pwm_shadow = [None; 3];
+        self.sleep_config = None;
+    }
+
+    /// Enable or disable verification of register writes.
+    ///
+    /// When enabled, every register written by the driver is read back and compared to the written
+    /// value, returning [`Error::VerifyMismatch`] if they differ. This catches flaky buses where
+    /// writes are acknowledged but don't stick, at the cost of roughly doubling the number of
+    /// transactions. The reset register is never verified, as it reads back the chip ID instead.
+    ///
+    /// Verification is disabled by default.
+    pub fn set_verify_writes(&mut self, verify_writes: bool) {
+        self.verify_writes = verify_writes;
+    }
+
+    /// Configure which LED drives which color component.
+    ///
+    /// The map is indexed by color component, so `[Led::Led2, Led::Led1, Led::Led0]` drives red
+    /// through LED 2, green through LED 1 and blue through LED 0. All methods taking or returning
+    /// RGB values consult this map, while methods addressing a single [`Led`] are unaffected. The
+    /// default maps red, green and blue to LED 0, 1 and 2 respectively.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the map does not contain every LED exactly once.
+    pub fn set_channel_map(&mut self, channel_map: [Led; 3]) {
+        assert!(
+            Led::ALL.iter().all(|led| channel_map.contains(led)),
+            "channel map must contain every LED exactly once"
+        );
+
+        self.channel_map = channel_map;
+    }
+
+    /// Change the max current of a single LED.
+    ///
+    /// The mode register is only written by the `set_*` methods, so the new current takes effect
+    /// with the next call to e.g. [`Aw2013::set_static`] or [`Aw2013::set_breathing`]. Re-apply the
+    /// current effect to change the current of a lit LED.
+    pub fn set_max_current(&mut self, led: Led, current: Current) {
+        self.max_currents[led as usize] = current;
+    }
+
+    /// Change the max currents of all LEDs.
+    ///
+    /// Just like with [`Aw2013::set_max_current`], the new currents take effect with the next call
+    /// to one of the `set_*` methods.
+    pub fn set_max_currents(&mut self, max_currents: [Current; 3]) {
+        self.max_currents = max_currents;
+    }
+
+    /// Limit the PWM value of a single LED to a maximum.
+    ///
+    /// Every subsequent PWM write for this LED is clamped to `max`, so even a requested brightness
+    /// of 255 results in `max`. This caps the duty cycle only, unlike lowering the max current it
+    /// does not change color temperature. The ceiling is applied to the final value written to the
+    /// PWM register, after any other scaling such as [`gamma_correct`], and also bounds the peak
+    /// brightness of breathing effects.
+    ///
+    /// The ceiling is `255` by default, which means no limit. Values already written to the
+    /// controller are not changed until the next write.
+    pub fn set_pwm_ceiling(&mut self, led: Led, max: u8) {
+        self.pwm_ceilings[led as usize] = max;
+    }
+
+    /// Check whether an AW2013 responds at the configured address.
+    ///
+    /// This reads the reset register (`0x00`), which returns the chip ID `0x33` on the AW2013. The
+    /// read has no side effects. Returns `false` if no device acknowledges the address or the
+    /// returned value differs from the chip ID, other bus errors are passed on.
+    pub fn probe(&mut self) -> Result<bool, Error<I::Error>> {
+        match self.read_register(REG_RESET) {
+            Ok(id) => Ok(id == CHIP_ID),
+            Err(Error::I2c(error)) if matches!(error.kind(), ErrorKind::NoAcknowledge(_)) => {
+                Ok(false)
+            }
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Reset the controller and enable it again.
+    ///
+    /// This is equivalent to calling [`Aw2013::reset`] followed by [`Aw2013::enable`]. No delay is
+    /// inserted between the two writes, so the chip may not have settled yet and ignore the enable.
+    /// Prefer [`Aw2013::init_with_delay`] whenever a delay provider is available.
+    pub fn init(&mut self) -> Result<(), Error<I::Error>> {
+        self.reset()?;
+        self.enable()
+    }
+
+    /// Reset the controller, wait for it to settle and enable it again.
+    ///
+    /// This is the recommended initialization sequence, equivalent to calling
+    /// [`Aw2013::reset_with_delay`] followed by [`Aw2013::enable`].
+    pub fn init_with_delay(&mut self, delay: &mut impl DelayNs) -> Result<(), Error<I::Error>> {
+        self.reset_with_delay(delay)?;
+        self.enable()
     }
 
     /// Reset the controller to its default state.
     ///
     /// Remember to enable the controller again after the reset if you plan to use it further.
-    pub fn reset(&mut self) -> Result<(), I::Error> {
+    pub fn reset(&mut self) -> Result<(), Error<I::Error>> {
         self.write_register(REG_RESET, LED_RESET_MASK)
     }
 
+    /// Reset the controller and wait for it to settle.
+    ///
+    /// The chip needs a short time after a software reset before it reliably accepts new writes.
+    /// This performs the reset just like [`Aw2013::reset`] and then waits about 1ms.
+    pub fn reset_with_delay(&mut self, delay: &mut impl DelayNs) -> Result<(), Error<I::Error>> {
+        self.reset()?;
+        delay.delay_us(RESET_DELAY_US);
+        Ok(())
+    }
+
     /// Enable the LED controller.
-    pub fn enable(&mut self) -> Result<(), I::Error> {
-        self.write_register(REG_GLOBAL_CONTROL, LED_MODULE_ENABLE_MASK)
+    pub fn enable(&mut self) -> Result<(), Error<I::Error>> {
+        self.enable_with_config(GlobalConfig {
+            enable: true,
+            ..GlobalConfig::default()
+        })
+    }
+
+    /// Check whether the LED controller is currently enabled.
+    pub fn is_enabled(&mut self) -> Result<bool, Error<I::Error>> {
+        Ok(self.read_register(REG_GLOBAL_CONTROL)? & LED_MODULE_ENABLE_MASK != 0)
+    }
+
+    /// Write a full configuration to the global control register.
+    ///
+    /// Unlike [`Aw2013::enable`], this lets you control every bit of the register.
+    pub fn enable_with_config(&mut self, config: GlobalConfig) -> Result<(), Error<I::Error>> {
+        self.write_register(REG_GLOBAL_CONTROL, config.to_register())
     }
 
     /// Disable the LED controller.
-    pub fn disable(&mut self) -> Result<(), I::Error> {
+    pub fn disable(&mut self) -> Result<(), Error<I::Error>> {
         self.write_register(REG_GLOBAL_CONTROL, 0)
     }
 
+    /// Turn off all LEDs at once.
+    ///
+    /// This clears the LED enable register in a single transaction, so all channels go dark
+    /// simultaneously. The mode, PWM and timing registers are left untouched.
+    pub fn all_off(&mut self) -> Result<(), Error<I::Error>> {
+        self.write_register(REG_LED_ENABLE, 0x0)
+    }
+
+    /// Disable a single LED.
+    ///
+    /// Only the enable bit of this LED is cleared, all other LEDs and the LED's mode and PWM
+    /// registers stay untouched.
+    pub fn disable_led(&mut self, led: Led) -> Result<(), Error<I::Error>> {
+        let enable_value = self.read_register(REG_LED_ENABLE)?;
+        self.write_register(REG_LED_ENABLE, enable_value & (!(1 << (led as u8))))
+    }
+
+    /// Enable a single LED.
+    ///
+    /// Only the enable bit of this LED is set, all other LEDs and the LED's mode and PWM registers
+    /// stay untouched. Together with [`Aw2013::set_pwm`] or [`Aw2013::write_raw`], this allows to
+    /// configure an LED while it is dark and turn it on at a precise moment.
+    pub fn enable_led(&mut self, led: Led) -> Result<(), Error<I::Error>> {
+        let enable_value = self.read_register(REG_LED_ENABLE)?;
+        self.write_register(REG_LED_ENABLE, enable_value | (1 << (led as u8)))
+    }
+
+    /// Run a self-test which lights each LED in sequence.
+    ///
+    /// The controller is enabled and each LED is lit on its own at full PWM but with the lowest
+    /// non-zero current of 5mA, while the caller supplied `step` hook is invoked, e.g. to wait or to
+    /// check the LED with a sensor. Afterwards the controller is left enabled with all LEDs off, a
+    /// PWM of zero and their previous mode registers restored. The configured max currents are not
+    /// changed.
+    pub fn self_test(&mut self, step: &mut dyn FnMut()) -> Result<(), Error<I::Error>> {
+        self.enable()?;
+        self.all_off()?;
+
+        for led in Led::ALL {
+            let mode = self.read_register(REG_LED_MODE_BASE + (led as u8))?;
+
+            self.write_register(REG_LED_MODE_BASE + (led as u8), Current::Five as u8)?;
+            self.set_pwm(led, 0xff)?;
+            self.write_register(REG_LED_ENABLE, 1 << (led as u8))?;
+            step();
+            self.all_off()?;
+
+            self.set_pwm(led, 0)?;
+            self.write_register(REG_LED_MODE_BASE + (led as u8), mode)?;
+        }
+
+        Ok(())
+    }
+
+    /// Put the controller into its lowest-power state.
+    ///
+    /// The full register configuration is read via [`Aw2013::read_config`] and kept in the driver,
+    /// then the controller is disabled. While disabled, the internal oscillator is stopped and all
+    /// outputs are off. The datasheet does not state whether the registers are retained in this
+    /// state, so the driver does not rely on it: [`Aw2013::wake`] writes the saved configuration back
+    /// either way.
+    ///
+    /// Calling this again while already asleep keeps the configuration saved by the first call.
+    pub fn sleep(&mut self) -> Result<(), Error<I::Error>> {
+        if self.sleep_config.is_none() {
+            self.sleep_config = Some(self.read_config()?);
+        }
+
+        self.disable()
+    }
+
+    /// Wake the controller up again after [`Aw2013::sleep`].
+    ///
+    /// The controller is enabled and the configuration saved when going to sleep is applied via
+    /// [`Aw2013::apply_config`], which restarts any breathing effects. Without a prior sleep, this
+    /// only enables the controller.
+    pub fn wake(&mut self) -> Result<(), Error<I::Error>> {
+        self.enable()?;
+
+        if let Some(mut config) = self.sleep_config {
+            config.global_control |= LED_MODULE_ENABLE_MASK;
+            self.apply_config(&config)?;
+            self.sleep_config = None;
+        }
+
+        Ok(())
+    }
+
     /// Set a static RGB value for all LEDs.
     ///
     /// You can optionally define fade-in and fade-out effects to fade to or from other values.
+    ///
+    /// Unlike [`Aw2013::set_static`], a brightness of zero does not disable the LED. All LEDs stay
+    /// enabled with their PWM set to zero instead, so transitions through black don't toggle the
+    /// enable register and cause flicker. Use [`Aw2013::all_off`] to actually turn them off.
+    ///
+    /// The fade times are written identically to every channel before any brightness changes. All
+    /// three PWM values are then written in a single burst, followed by a single write of the enable
+    /// register, so the channels start their ramps together and cross-fades between colors stay
+    /// coherent.
     pub fn set_static_rgb(
         &mut self,
         rgb: [u8; 3],
-        fade_in: Option<u8>,
-        fade_out: Option<u8>,
-    ) -> Result<(), I::Error> {
-        for led in [Led::Led0, Led::Led1, Led::Led2] {
-            self.set_static(led, rgb[led as usize], fade_in, fade_out)?;
+        fade_in: Option<FadeTime>,
+        fade_out: Option<FadeTime>,
+    ) -> Result<(), Error<I::Error>> {
+        let rgb = self.map_rgb(rgb);
+        let mut modes = [0; 3];
+
+        for led in Led::ALL {
+            modes[led as usize] = self.configure_fades(led, fade_in, fade_out)?;
         }
 
-        Ok(())
+        self.write_registers(REG_LED_MODE_BASE, &modes)?;
+        self.write_registers(REG_LED_PWM_BASE, &rgb)?;
+        self.write_register(REG_LED_ENABLE, 0x7)
+    }
+
+    /// Set a static RGB value for all LEDs from an `rgb` crate color.
+    ///
+    /// This is a shorthand for [`Aw2013::set_static_rgb`].
+    #[cfg(feature = "rgb")]
+    pub fn set_static_rgb8(
+        &mut self,
+        color: rgb::RGB8,
+        fade_in: Option<FadeTime>,
+        fade_out: Option<FadeTime>,
+    ) -> Result<(), Error<I::Error>> {
+        self.set_static_rgb([color.r, color.g, color.b], fade_in, fade_out)
+    }
+
+    /// Set a static value for some of the LEDs.
+    ///
+    /// Works like [`Aw2013::set_static_rgb`], except that components set to `None` leave their LED
+    /// completely untouched, without any bus traffic for that channel. LEDs which are set stay
+    /// enabled even with a brightness of zero.
+    pub fn set_static_rgb_partial(
+        &mut self,
+        rgb: [Option<u8>; 3],
+        fade_in: Option<FadeTime>,
+        fade_out: Option<FadeTime>,
+    ) -> Result<(), Error<I::Error>> {
+        let rgb = self.map_rgb(rgb);
+        let mut updated_leds = 0;
+
+        for led in Led::ALL {
+            if let Some(brightness) = rgb[led as usize] {
+                self.configure_static(led, brightness, fade_in, fade_out)?;
+                updated_leds |= 1 << (led as u8);
+            }
+        }
+
+        if updated_leds == 0 {
+            return Ok(());
+        }
+
+        self.modify_register(REG_LED_ENABLE, updated_leds, updated_leds)
+    }
+
+    /// Set a static HSV value for all LEDs.
+    ///
+    /// The color is converted through [`Rgb::from_hsv`] and then passed on to
+    /// [`Aw2013::set_static_rgb`].
+    pub fn set_static_hsv(
+        &mut self,
+        hsv: [u8; 3],
+        fade_in: Option<FadeTime>,
+        fade_out: Option<FadeTime>,
+    ) -> Result<(), Error<I::Error>> {
+        self.set_static_rgb(Rgb::from_hsv(hsv).into(), fade_in, fade_out)
+    }
+
+    /// Set a static RGB value for all LEDs with brightness percentages.
+    ///
+    /// Each percentage is clamped to 100 and scaled to the PWM range before being passed on to
+    /// [`Aw2013::set_static_rgb`].
+    pub fn set_static_rgb_percent(
+        &mut self,
+        percents: [u8; 3],
+        fade_in: Option<FadeTime>,
+        fade_out: Option<FadeTime>,
+    ) -> Result<(), Error<I::Error>> {
+        self.set_static_rgb(percents.map(percent_to_pwm), fade_in, fade_out)
+    }
+
+    /// Set a static value for a single LED with a brightness percentage.
+    ///
+    /// The percentage is clamped to 100 and scaled to the PWM range before being passed on to
+    /// [`Aw2013::set_static`].
+    pub fn set_static_percent(
+        &mut self,
+        led: Led,
+        percent: u8,
+        fade_in: Option<FadeTime>,
+        fade_out: Option<FadeTime>,
+    ) -> Result<(), Error<I::Error>> {
+        self.set_static(led, percent_to_pwm(percent), fade_in, fade_out)
     }
 
     /// Set a static value for a single LED.
     ///
     /// You can optionally define fade-in and fade-out effects to fade to or from other values.
+    /// The fade-in time is stored in the rise period and the fade-out time in the fall period of the
+    /// LED's timing registers. The other fields of these registers are preserved.
     pub fn set_static(
         &mut self,
         led: Led,
         brightness: u8,
-        fade_in: Option<u8>,
-        fade_out: Option<u8>,
-    ) -> Result<(), I::Error> {
+        fade_in: Option<FadeTime>,
+        fade_out: Option<FadeTime>,
+    ) -> Result<(), Error<I::Error>> {
         if brightness == 0 {
             return self.disable_led(led);
         }
 
-        let mut config: u8 = self.max_currents[led as usize] as u8;
+        self.configure_static(led, brightness, fade_in, fade_out)?;
+        self.enable_led(led)?;
 
-        if let Some(fade_in) = fade_in {
-            config |= LED_FADE_IN_MASK;
-            self.write_register(REG_TIMING_0_BASE + (led as u8) * 3, fade_in.min(7) << 4)?;
-        }
+        Ok(())
+    }
 
-        if let Some(fade_out) = fade_out {
-            config |= LED_FADE_OUT_MASK;
-            self.write_register(REG_TIMING_0_BASE + (led as u8) * 3, fade_out.min(7) << 4)?;
-        }
+    /// Set all LEDs to full brightness without any fade effects.
+    pub fn set_full_rgb(&mut self) -> Result<(), Error<I::Error>> {
+        self.set_static_rgb([0xff; 3], None, None)
+    }
 
-        self.write_register(REG_LED_MODE_BASE + (led as u8), config)?;
-        self.write_register(REG_LED_PWM_BASE + (led as u8), brightness)?;
+    /// Set a single LED to full brightness without any fade effects.
+    pub fn set_full(&mut self, led: Led) -> Result<(), Error<I::Error>> {
+        self.set_static(led, 0xff, None, None)
+    }
 
-        self.enable_led(led)?;
+    /// Update the brightness of a single LED.
+    ///
+    /// Only the PWM register is written, the mode and enable registers stay untouched. This makes it
+    /// suitable for animation loops with a high frame rate. It is the caller's responsibility to
+    /// have configured and enabled the LED before, e.g. by calling [`Aw2013::set_static`] at least
+    /// once.
+    pub fn set_pwm(&mut self, led: Led, brightness: u8) -> Result<(), Error<I::Error>> {
+        self.write_register(REG_LED_PWM_BASE + (led as u8), brightness)
+    }
 
-        Ok(())
+    /// Update the brightness of all LEDs in a single transaction.
+    ///
+    /// Only the PWM registers are written, the mode and enable registers stay untouched. It is the
+    /// caller's responsibility to have configured and enabled the LEDs before, e.g. through
+    /// [`Aw2013::set_static_rgb`].
+    pub fn set_pwm_rgb(&mut self, rgb: Rgb) -> Result<(), Error<I::Error>> {
+        self.write_registers(REG_LED_PWM_BASE, &self.map_rgb(rgb.into()))
     }
 
-    /// Set a breathing cycle RGB value for all LEDs.
-    pub fn set_breathing_rgb(&mut self, rgb: [u8; 3], timing: &Timing) -> Result<(), I::Error> {
-        self.write_register(REG_LED_ENABLE, 0x0)?;
+    /// Update the brightness of all LEDs, but only if it differs from what was last written.
+    ///
+    /// The driver keeps a shadow copy of every PWM value it writes. If all channels already hold
+    /// the requested values, no bus traffic happens at all. Otherwise a single burst write through
+    /// [`Aw2013::set_pwm_rgb`] is issued. Returns whether a write occurred.
+    ///
+    /// The shadow copy only knows about writes made through this driver instance. Until a channel
+    /// was written once, or after a failed write, it is considered unknown and always rewritten.
+    pub fn set_rgb_if_changed(&mut self, rgb: Rgb) -> Result<bool, Error<I::Error>> {
+        let mut values = self.map_rgb(rgb.into());
+        self.apply_pwm_ceilings(REG_LED_PWM_BASE, &mut values);
 
-        for led in [Led::Led0, Led::Led1, Led::Led2] {
-            self.write_register(
-                REG_LED_MODE_BASE + (led as u8),
-                self.max_currents[led as usize] as u8,
-            )?;
+        if self.pwm_shadow == values.map(Some) {
+            return Ok(false);
         }
 
-        for led in [Led::Led0, Led::Led1, Led::Led2] {
-            self.write_register(REG_LED_PWM_BASE + (led as u8), rgb[led as usize])?;
-            self.configure_timing(led, timing)?;
-        }
+        self.set_pwm_rgb(rgb)?;
+        Ok(true)
+    }
 
-        for led in [Led::Led0, Led::Led1, Led::Led2] {
-            self.write_register(
-                REG_LED_MODE_BASE + (led as u8),
-                self.max_currents[led as usize] as u8 | LED_BREATHE_MODE_MASK,
-            )?;
+    /// Gradually morph all LEDs from one color to another.
+    ///
+    /// The color is linearly interpolated in RGB space over `steps` steps, which are spread evenly
+    /// across `total_ms` milliseconds. This is [`Aw2013::cross_fade_rgb`] with a fixed delay between
+    /// the steps, so the same requirements apply. This blocks until the final color is reached.
+    pub fn morph(
+        &mut self,
+        from: Rgb,
+        to: Rgb,
+        total_ms: u32,
+        steps: u16,
+        delay: &mut impl DelayNs,
+    ) -> Result<(), Error<I::Error>> {
+        let step_ms = total_ms.checked_div(steps as u32).unwrap_or(0);
+        self.cross_fade_rgb(from.into(), to.into(), steps, &mut || {
+            delay.delay_ms(step_ms)
+        })
+    }
+
+    /// Cross-fade all LEDs from one color to another.
+    ///
+    /// Each channel is linearly interpolated over `steps` steps, with the caller supplied `step`
+    /// hook being invoked before each intermediate color is written, e.g. to wait between steps.
+    /// The colors are written through [`Aw2013::set_pwm_rgb`], so the LEDs must already be configured
+    /// and enabled. With zero steps, the target color is written right away.
+    pub fn cross_fade_rgb(
+        &mut self,
+        from: [u8; 3],
+        to: [u8; 3],
+        steps: u16,
+        step: &mut dyn FnMut(),
+    ) -> Result<(), Error<I::Error>> {
+        if steps == 0 {
+            return self.set_pwm_rgb(to.into());
         }
 
-        let mut active_leds = 0;
+        for current in 1..=steps {
+            step();
 
-        for (i, value) in rgb.iter().enumerate() {
-            if *value > 0 {
-                active_leds |= 1 << i;
+            let mut rgb = [0; 3];
+
+            for (i, value) in rgb.iter_mut().enumerate() {
+                let distance = to[i] as i32 - from[i] as i32;
+                *value = (from[i] as i32 + distance * current as i32 / steps as i32) as u8;
             }
-        }
 
-        self.write_register(REG_LED_ENABLE, active_leds)?;
+            self.set_pwm_rgb(rgb.into())?;
+        }
 
         Ok(())
     }
 
+    /// Set a breathing cycle RGB value for all LEDs.
+    pub fn set_breathing_rgb(
+        &mut self,
+        rgb: [u8; 3],
+        timing: &Timing,
+    ) -> Result<(), Error<I::Error>> {
+        self.set_breathing_rgb_per_led(rgb, [timing; 3])
+    }
+
+    /// Set a breathing cycle RGB value for all LEDs from an `rgb` crate color.
+    ///
+    /// This is a shorthand for [`Aw2013::set_breathing_rgb`].
+    #[cfg(feature = "rgb")]
+    pub fn set_breathing_rgb8(
+        &mut self,
+        color: rgb::RGB8,
+        timing: &Timing,
+    ) -> Result<(), Error<I::Error>> {
+        self.set_breathing_rgb([color.r, color.g, color.b], timing)
+    }
+
+    /// Set a breathing cycle RGB value for all LEDs, with a separate timing for each LED.
+    ///
+    /// The timings are indexed like the RGB components. Just like [`Aw2013::set_breathing_rgb`], all
+    /// LEDs are only enabled once every channel is fully configured.
+    pub fn set_breathing_rgb_per_led(
+        &mut self,
+        rgb: [u8; 3],
+        timings: [&Timing; 3],
+    ) -> Result<(), Error<I::Error>> {
+        self.stage_breathing_rgb(rgb, timings)?;
+        self.write_register(REG_LED_ENABLE, self.breathing_enable_mask(rgb))
+    }
+
     /// Set a breathing cycle value for a single LED.
     pub fn set_breathing(
         &mut self,
         led: Led,
         brightness: u8,
         timing: &Timing,
-    ) -> Result<(), I::Error> {
+    ) -> Result<(), Error<I::Error>> {
         self.disable_led(led)?;
 
         if brightness == 0 {
@@ -267,40 +869,832 @@ where
         Ok(())
     }
 
-    fn configure_timing(&mut self, led: Led, timing: &Timing) -> Result<(), I::Error> {
-        self.write_register(
-            REG_TIMING_0_BASE + (led as u8) * 3,
-            timing.rise.min(7) << 4 | timing.hold.min(5),
-        )?;
+    /// Set a breathing cycle for a single LED between two brightness bounds.
+    ///
+    /// The controller always fades from zero up to the value in the PWM register and back down to
+    /// zero. There is no register for a lower bound, so `min` must be zero. Any other value is
+    /// rejected with [`Error::UnsupportedBreathingFloor`] without touching the controller. A dim glow
+    /// between cycles cannot be achieved in hardware and has to be animated in software instead.
+    pub fn set_breathing_range(
+        &mut self,
+        led: Led,
+        min: u8,
+        max: u8,
+        timing: &Timing,
+    ) -> Result<(), Error<I::Error>> {
+        if min != 0 {
+            return Err(Error::UnsupportedBreathingFloor(min));
+        }
+
+        self.set_breathing(led, max, timing)
+    }
+
+    /// Stop an ongoing breathing cycle of a single LED and hold it at a fixed brightness.
+    ///
+    /// The PWM register is written first while the LED is still breathing, then the mode register
+    /// is switched to static output with the configured max current, which also clears any fade
+    /// flags. The LED is never disabled in between, so it goes straight to `hold_at` without a
+    /// visible jump through zero.
+    pub fn stop_breathing(&mut self, led: Led, hold_at: u8) -> Result<(), Error<I::Error>> {
+        self.write_register(REG_LED_PWM_BASE + (led as u8), hold_at)?;
         self.write_register(
-            REG_TIMING_1_BASE + (led as u8) * 3,
-            timing.fall.min(7) << 4 | timing.off.min(7),
+            REG_LED_MODE_BASE + (led as u8),
+            self.max_currents[led as usize] as u8,
         )?;
+        self.enable_led(led)
+    }
+
+    /// Blink a single LED on and off.
+    ///
+    /// This uses the breathing mode with the shortest possible rise and fall periods of 0.13s, so
+    /// the edges are close to, but not exactly, a square wave. `on_time` uses the steps of
+    /// [`Timing::hold`] and `off_time` those of [`Timing::off`], while `cycles` works just like
+    /// [`Timing::cycles`].
+    pub fn set_blink(
+        &mut self,
+        led: Led,
+        brightness: u8,
+        on_time: u8,
+        off_time: u8,
+        cycles: u8,
+    ) -> Result<(), Error<I::Error>> {
+        self.set_breathing(
+            led,
+            brightness,
+            &Timing {
+                delay: 0,
+                rise: 0,
+                hold: on_time,
+                fall: 0,
+                off: off_time,
+                cycles,
+            },
+        )
+    }
+
+    /// Set a breathing cycle RGB value for all LEDs, rejecting out-of-range timings.
+    ///
+    /// Unlike [`Aw2013::set_breathing_rgb`], the timing is validated before touching the bus and an
+    /// [`Error::InvalidTiming`] is returned instead of clamping.
+    pub fn set_breathing_rgb_checked(
+        &mut self,
+        rgb: [u8; 3],
+        timing: &Timing,
+    ) -> Result<(), Error<I::Error>> {
+        timing.validate()?;
+        self.set_breathing_rgb(rgb, timing)
+    }
+
+    /// Set a breathing cycle value for a single LED, rejecting out-of-range timings.
+    ///
+    /// Unlike [`Aw2013::set_breathing`], the timing is validated before touching the bus and an
+    /// [`Error::InvalidTiming`] is returned instead of clamping.
+    pub fn set_breathing_checked(
+        &mut self,
+        led: Led,
+        brightness: u8,
+        timing: &Timing,
+    ) -> Result<(), Error<I::Error>> {
+        timing.validate()?;
+        self.set_breathing(led, brightness, timing)
+    }
+
+    /// Blink a single LED `count` times to signal a numeric code, e.g. an error code.
+    ///
+    /// Each blink lights the LED at full brightness for 250ms, followed by 250ms off. After the last
+    /// blink the LED stays off for another 1000ms, so that repeated codes can be told apart. This
+    /// blocks for `count * 500ms + 1000ms` in total and leaves the LED enabled with a brightness of
+    /// zero.
+    pub fn blink_code(
+        &mut self,
+        led: Led,
+        count: u8,
+        delay: &mut impl DelayNs,
+    ) -> Result<(), Error<I::Error>> {
+        self.set_pwm(led, 0)?;
         self.write_register(
-            REG_TIMING_2_BASE + (led as u8) * 3,
-            timing.delay.min(7) << 4 | timing.cycles.min(15),
+            REG_LED_MODE_BASE + (led as u8),
+            self.max_currents[led as usize] as u8,
         )?;
+        self.enable_led(led)?;
+
+        for _ in 0..count {
+            self.set_pwm(led, 0xff)?;
+            delay.delay_ms(BLINK_CODE_ON_MS);
+            self.set_pwm(led, 0)?;
+            delay.delay_ms(BLINK_CODE_OFF_MS);
+        }
+
+        delay.delay_ms(BLINK_CODE_GAP_MS);
 
         Ok(())
     }
 
-    fn disable_led(&mut self, led: Led) -> Result<(), I::Error> {
+    /// Read which LEDs are currently enabled, indexed by [`Led`].
+    pub fn enabled_leds(&mut self) -> Result<[bool; 3], Error<I::Error>> {
         let enable_value = self.read_register(REG_LED_ENABLE)?;
-        self.write_register(REG_LED_ENABLE, enable_value & (!(1 << (led as u8))))
+        Ok(Led::ALL.map(|led| enable_value & (1 << (led as u8)) != 0))
+    }
+
+    /// Check whether a single LED has completed its programmed breathing cycles.
+    ///
+    /// This reads the status register (`0x02`) and consults bit `n` for LED `n`, which the
+    /// controller sets once a channel finished a finite number of breathing cycles. With infinite
+    /// cycles (`cycles: 0`) the bit is never set.
+    ///
+    /// Status registers of this kind are commonly cleared by reading them, and it is not confirmed
+    /// whether that applies here. Treat every call as consuming the flags: once this returned `true`,
+    /// a later call may return `false` again. If reading does clear the flags, checking the LEDs one
+    /// after the other can also miss flags consumed by an earlier call. Read
+    /// [`registers::REG_STATUS`] once via [`Aw2013::read_raw`] when all flags are needed together.
+    pub fn is_breathing_done(&mut self, led: Led) -> Result<bool, Error<I::Error>> {
+        Ok(self.read_register(REG_STATUS)? & (1 << (led as u8)) != 0)
     }
 
-    fn enable_led(&mut self, led: Led) -> Result<(), I::Error> {
+    /// Read the max current of a single LED as currently configured on the controller.
+    ///
+    /// This decodes the mode register, so it may differ from the max current stored in the driver,
+    /// e.g. after a reset or before the LED was first configured.
+    pub fn read_max_current(&mut self, led: Led) -> Result<Current, Error<I::Error>> {
+        Ok(Current::from_bits(
+            self.read_register(REG_LED_MODE_BASE + (led as u8))?,
+        ))
+    }
+
+    /// Estimate the total current currently sourced by the controller in milliamps.
+    ///
+    /// For every enabled LED, the max current read from its mode register is scaled by its PWM duty
+    /// cycle and the results are summed up. This is an approximation which assumes that the
+    /// current scales linearly with the duty cycle. For breathing LEDs the peak current is reported.
+    /// If the controller itself is disabled, the estimate is zero.
+    pub fn estimated_current_ma(&mut self) -> Result<u16, Error<I::Error>> {
+        if !self.is_enabled()? {
+            return Ok(0);
+        }
+
+        let enabled_leds = self.enabled_leds()?;
+        let mut total = 0;
+
+        for led in Led::ALL {
+            if !enabled_leds[led as usize] {
+                continue;
+            }
+
+            let current = self.read_max_current(led)?.milliamps() as u16;
+            let pwm = self.get_brightness(led)? as u16;
+            total += current * pwm;
+        }
+
+        Ok(total / 0xff)
+    }
+
+    /// Read the current PWM brightness of a single LED.
+    pub fn get_brightness(&mut self, led: Led) -> Result<u8, Error<I::Error>> {
+        self.read_register(REG_LED_PWM_BASE + (led as u8))
+    }
+
+    /// Read the current PWM brightness of all LEDs.
+    pub fn get_brightness_rgb(&mut self) -> Result<[u8; 3], Error<I::Error>> {
+        let mut rgb = [0; 3];
+
+        for led in Led::ALL {
+            rgb[led as usize] = self.get_brightness(led)?;
+        }
+
+        Ok(self.unmap_rgb(rgb))
+    }
+
+    /// Take a snapshot of the current LED output state.
+    pub fn snapshot(&mut self) -> Result<DeviceState, Error<I::Error>> {
+        self.read_led_registers()
+    }
+
+    /// Restore a previously taken snapshot.
+    ///
+    /// Channels which are off in the snapshot are disabled first, then the timing, PWM and mode
+    /// registers are written and the enable register last. Thus no channel lights up with a partial
+    /// configuration, while channels which are on both now and in the snapshot are not toggled.
+    /// Breathing and fade effects come back with their original timing, even if another effect
+    /// changed it in the meantime.
+    pub fn restore(&mut self, state: &DeviceState) -> Result<(), Error<I::Error>> {
         let enable_value = self.read_register(REG_LED_ENABLE)?;
-        self.write_register(REG_LED_ENABLE, enable_value | (1 << (led as u8)))
+        self.write_register(REG_LED_ENABLE, enable_value & state.led_enable)?;
+
+        self.write_channel_registers(&state.timings, &state.pwms, &state.modes)?;
+        self.write_register(REG_LED_ENABLE, state.led_enable)
     }
 
-    fn write_register(&mut self, address: u8, data: u8) -> Result<(), I::Error> {
-        self.i2c.write(self.address, &[address, data])
+    /// Read all LED related registers for debugging.
+    ///
+    /// This is the same as [`Aw2013::snapshot`]. Print the result with `{:?}` for a readable dump.
+    pub fn dump_registers(&mut self) -> Result<RegisterDump, Error<I::Error>> {
+        self.read_led_registers()
+    }
+
+    /// Read the full register configuration of the controller.
+    pub fn read_config(&mut self) -> Result<Aw2013Config, Error<I::Error>> {
+        let global_control = self.read_register(REG_GLOBAL_CONTROL)?;
+        let state = self.read_led_registers()?;
+
+        Ok(Aw2013Config {
+            global_control,
+            led_enable: state.led_enable,
+            leds: Led::ALL.map(|led| LedConfig {
+                mode: state.modes[led as usize],
+                pwm: state.pwms[led as usize],
+                timing: state.timings[led as usize],
+            }),
+        })
+    }
+
+    /// Apply a full register configuration to the controller.
+    ///
+    /// All LEDs are disabled while the channel registers are written and only enabled again once
+    /// everything is in place, so no channel lights up with a partially applied configuration.
+    pub fn apply_config(&mut self, config: &Aw2013Config) -> Result<(), Error<I::Error>> {
+        self.write_register(REG_LED_ENABLE, 0x0)?;
+
+        self.write_channel_registers(
+            &config.leds.map(|led| led.timing),
+            &config.leds.map(|led| led.pwm),
+            &config.leds.map(|led| led.mode),
+        )?;
+
+        self.write_register(REG_GLOBAL_CONTROL, config.global_control)?;
+        self.write_register(REG_LED_ENABLE, config.led_enable)?;
+
+        Ok(())
+    }
+
+    /// Write the timing, PWM and mode registers of all LEDs, in that order.
+    fn write_channel_registers(
+        &mut self,
+        timings: &[[u8; 3]; 3],
+        pwms: &[u8; 3],
+        modes: &[u8; 3],
+    ) -> Result<(), Error<I::Error>> {
+        for led in Led::ALL {
+            self.write_registers(REG_TIMING_0_BASE + (led as u8) * 3, &timings[led as usize])?;
+        }
+
+        for led in Led::ALL {
+            self.write_register(REG_LED_PWM_BASE + (led as u8), pwms[led as usize])?;
+            self.write_register(REG_LED_MODE_BASE + (led as u8), modes[led as usize])?;
+        }
+
+        Ok(())
+    }
+
+    /// Configure all LEDs for a breathing cycle while leaving them disabled.
+    ///
+    /// Writing [`Aw2013::breathing_enable_mask`] to the LED enable register starts the cycle.
+    pub(crate) fn stage_breathing_rgb(
+        &mut self,
+        rgb: [u8; 3],
+        timings: [&Timing; 3],
+    ) -> Result<(), Error<I::Error>> {
+        let rgb = self.map_rgb(rgb);
+        let timings = self.map_rgb(timings);
+
+        self.write_register(REG_LED_ENABLE, 0x0)?;
+
+        for led in Led::ALL {
+            self.write_register(
+                REG_LED_MODE_BASE + (led as u8),
+                self.max_currents[led as usize] as u8,
+            )?;
+        }
+
+        for led in Led::ALL {
+            self.write_register(REG_LED_PWM_BASE + (led as u8), rgb[led as usize])?;
+            self.configure_timing(led, timings[led as usize])?;
+        }
+
+        for led in Led::ALL {
+            self.write_register(
+                REG_LED_MODE_BASE + (led as u8),
+                self.max_currents[led as usize] as u8 | LED_BREATHE_MODE_MASK,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Value of the LED enable register for a breathing cycle, enabling all lit LEDs.
+    pub(crate) fn breathing_enable_mask(&self, rgb: [u8; 3]) -> u8 {
+        let mut active_leds = 0;
+
+        for (i, value) in self.map_rgb(rgb).iter().enumerate() {
+            if *value > 0 {
+                active_leds |= 1 << i;
+            }
+        }
+
+        active_leds
+    }
+
+    /// Distribute RGB components to the physical LEDs according to the channel map.
+    fn map_rgb<T: Copy>(&self, rgb: [T; 3]) -> [T; 3] {
+        let mut physical = rgb;
+
+        for (component, led) in self.channel_map.iter().enumerate() {
+            physical[*led as usize] = rgb[component];
+        }
+
+        physical
+    }
+
+    /// Collect RGB components from the physical LEDs according to the channel map.
+    fn unmap_rgb<T: Copy>(&self, physical: [T; 3]) -> [T; 3] {
+        self.channel_map.map(|led| physical[led as usize])
+    }
+
+    /// Write the fade, mode and PWM registers of a static LED, without enabling it.
+    fn configure_static(
+        &mut self,
+        led: Led,
+        brightness: u8,
+        fade_in: Option<FadeTime>,
+        fade_out: Option<FadeTime>,
+    ) -> Result<(), Error<I::Error>> {
+        let config = self.configure_fades(led, fade_in, fade_out)?;
+        self.write_register(REG_LED_MODE_BASE + (led as u8), config)?;
+        self.write_register(REG_LED_PWM_BASE + (led as u8), brightness)?;
+
+        Ok(())
     }
 
-    fn read_register(&mut self, address: u8) -> Result<u8, I::Error> {
+    /// Write the fade times of a static LED and return the matching value of its mode register.
+    fn configure_fades(
+        &mut self,
+        led: Led,
+        fade_in: Option<FadeTime>,
+        fade_out: Option<FadeTime>,
+    ) -> Result<u8, Error<I::Error>> {
+        let mut config: u8 = self.max_currents[led as usize] as u8;
+
+        if let Some(fade_in) = fade_in {
+            config |= LED_FADE_IN_MASK;
+            self.modify_register(
+                REG_TIMING_0_BASE + (led as u8) * 3,
+                0xf0,
+                (fade_in as u8) << 4,
+            )?;
+        }
+
+        if let Some(fade_out) = fade_out {
+            config |= LED_FADE_OUT_MASK;
+            self.modify_register(
+                REG_TIMING_1_BASE + (led as u8) * 3,
+                0xf0,
+                (fade_out as u8) << 4,
+            )?;
+        }
+
+        Ok(config)
+    }
+
+    fn configure_timing(&mut self, led: Led, timing: &Timing) -> Result<(), Error<I::Error>> {
+        let [timing_0, timing_1, timing_2] = timing.to_registers();
+        self.write_register(REG_TIMING_0_BASE + (led as u8) * 3, timing_0)?;
+        self.write_register(REG_TIMING_1_BASE + (led as u8) * 3, timing_1)?;
+        self.write_register(REG_TIMING_2_BASE + (led as u8) * 3, timing_2)?;
+
+        Ok(())
+    }
+
+    /// Write a raw value to a register.
+    ///
+    /// This is meant for features which are not modelled by the driver, see [`registers`] for the
+    /// known register addresses. The write goes through the same path as all other writes, so PWM
+    /// ceilings and write verification still apply.
+    pub fn write_raw(&mut self, register: u8, value: u8) -> Result<(), Error<I::Error>> {
+        self.write_register(register, value)
+    }
+
+    /// Read the raw value of a register.
+    pub fn read_raw(&mut self, register: u8) -> Result<u8, Error<I::Error>> {
+        self.read_register(register)
+    }
+
+    /// Start a batch of register writes.
+    ///
+    /// Nothing is written to the bus until [`Batch::flush`] is called.
+    pub fn begin_batch(&mut self) -> Batch<'_, I> {
+        Batch::new(self)
+    }
+
+    fn modify_register(&mut self, address: u8, mask: u8, value: u8) -> Result<(), Error<I::Error>> {
+        let current = self.read_register(address)?;
+        self.write_register(address, current & !mask | value & mask)
+    }
+
+    pub(crate) fn write_register(&mut self, address: u8, data: u8) -> Result<(), Error<I::Error>> {
+        let mut data = [data];
+        self.apply_pwm_ceilings(address, &mut data);
+        let [data] = data;
+
+        let result = self
+            .i2c
+            .write(self.address, &[address, data])
+            .map_err(Error::I2c)
+            .and_then(|_| self.verify_registers(address, &[data]));
+        self.update_pwm_shadow(address, &[data], result.is_ok());
+        result
+    }
+
+    /// Write a single register without applying PWM ceilings, updating the shadow or verifying it.
+    ///
+    /// Only meant for registers outside of the PWM range, with `verify_registers` being
+    /// called separately afterwards.
+    pub(crate) fn write_register_unverified(
+        &mut self,
+        address: u8,
+        data: u8,
+    ) -> Result<(), Error<I::Error>> {
+        self.i2c
+            .write(self.address, &[address, data])
+            .map_err(Error::I2c)
+    }
+
+    /// Write consecutive registers in a single transaction, relying on the address auto-increment.
+    fn write_registers(&mut self, address: u8, data: &[u8]) -> Result<(), Error<I::Error>> {
+        let len = data.len();
+        let mut buffer = [0; REGISTER_COUNT + 1];
+        buffer[0] = address;
+        buffer[1..=len].copy_from_slice(data);
+        self.apply_pwm_ceilings(address, &mut buffer[1..=len]);
+
+        let result = self
+            .i2c
+            .write(self.address, &buffer[..=len])
+            .map_err(Error::I2c)
+            .and_then(|_| self.verify_registers(address, &buffer[1..=len]));
+        self.update_pwm_shadow(address, &buffer[1..=len], result.is_ok());
+        result
+    }
+
+    fn apply_pwm_ceilings(&self, address: u8, data: &mut [u8]) {
+        for (i, value) in data.iter_mut().enumerate() {
+            let reg = address + i as u8;
+
+            if (REG_LED_PWM_BASE..REG_LED_PWM_BASE + 3).contains(&reg) {
+                *value = (*value).min(self.pwm_ceilings[(reg - REG_LED_PWM_BASE) as usize]);
+            }
+        }
+    }
+
+    fn update_pwm_shadow(&mut self, address: u8, data: &[u8], written: bool) {
+        for (i, value) in data.iter().enumerate() {
+            let reg = address + i as u8;
+
+            if reg == REG_RESET && *value == LED_RESET_MASK {
+                // A reset clears all PWM registers, no matter which path it was sent through
+                self.pwm_shadow = [written.then_some(0); 3];
+            } else if (REG_LED_PWM_BASE..REG_LED_PWM_BASE + 3).contains(&reg) {
+                self.pwm_shadow[(reg - REG_LED_PWM_BASE) as usize] = written.then_some(*value);
+            }
+        }
+    }
+
+    pub(crate) fn verify_registers(
+        &mut self,
+        address: u8,
+        data: &[u8],
+    ) -> Result<(), Error<I::Error>> {
+        if !self.verify_writes {
+            return Ok(());
+        }
+
+        for (i, expected) in data.iter().enumerate() {
+            let reg = address + i as u8;
+
+            if reg == REG_RESET {
+                continue;
+            }
+
+            let got = self.read_register(reg)?;
+
+            if got != *expected {
+                return Err(Error::VerifyMismatch {
+                    reg,
+                    expected: *expected,
+                    got,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Read the enable, mode, PWM and timing registers of all LEDs.
+    fn read_led_registers(&mut self) -> Result<DeviceState, Error<I::Error>> {
+        let mut state = DeviceState {
+            led_enable: self.read_register(REG_LED_ENABLE)?,
+            modes: [0; 3],
+            pwms: [0; 3],
+            timings: [[0; 3]; 3],
+        };
+
+        for led in Led::ALL {
+            let index = led as usize;
+            state.modes[index] = self.read_register(REG_LED_MODE_BASE + (led as u8))?;
+            state.pwms[index] = self.read_register(REG_LED_PWM_BASE + (led as u8))?;
+
+            for (i, timing) in state.timings[index].iter_mut().enumerate() {
+                *timing = self.read_register(REG_TIMING_0_BASE + (led as u8) * 3 + i as u8)?;
+            }
+        }
+
+        Ok(state)
+    }
+
+    fn read_register(&mut self, address: u8) -> Result<u8, Error<I::Error>> {
         let mut buffer: [u8; 1] = [0];
-        self.i2c.write_read(self.address, &[address], &mut buffer)?;
+        self.i2c
+            .write_read(self.address, &[address], &mut buffer)
+            .map_err(Error::I2c)?;
         Ok(buffer[0])
     }
 }
+
+fn percent_to_pwm(percent: u8) -> u8 {
+    (percent.min(100) as u16 * 0xff / 100) as u8
+}
+
+fn assert_valid_address(address: u8) {
+    assert!(
+        address <= 0x7f,
+        "address {:#04x} is not a valid 7-bit I2C address",
+        address
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockI2c;
+
+    fn driver() -> Aw2013<MockI2c> {
+        Aw2013::from_default_address(MockI2c::new(), [Current::Five; 3])
+    }
+
+    #[test]
+    fn wake_after_repeated_sleep_enables_controller() {
+        let mut aw2013 = driver();
+        aw2013.init().unwrap();
+        aw2013.set_static(Led::Led0, 0x80, None, None).unwrap();
+
+        aw2013.sleep().unwrap();
+        aw2013.sleep().unwrap();
+        aw2013.wake().unwrap();
+
+        assert!(aw2013.is_enabled().unwrap());
+        assert_eq!(aw2013.get_brightness(Led::Led0).unwrap(), 0x80);
+        assert_eq!(aw2013.enabled_leds().unwrap(), [true, false, false]);
+    }
+
+    #[test]
+    fn static_fades_merge_into_timing_registers() {
+        let mut aw2013 = driver();
+        aw2013.i2c.set_register(REG_TIMING_0_BASE + 3, 0x75);
+        aw2013.i2c.set_register(REG_TIMING_1_BASE + 3, 0x72);
+
+        aw2013
+            .set_static(
+                Led::Led1,
+                0x80,
+                Some(FadeTime::Ms1040),
+                Some(FadeTime::Ms260),
+            )
+            .unwrap();
+
+        assert_eq!(aw2013.i2c.register(REG_TIMING_0_BASE + 3), 0x35);
+        assert_eq!(aw2013.i2c.register(REG_TIMING_1_BASE + 3), 0x12);
+    }
+
+    #[test]
+    fn static_rgb_through_zero_keeps_leds_enabled() {
+        let mut aw2013 = driver();
+        aw2013.set_static_rgb([128, 0, 255], None, None).unwrap();
+        aw2013.set_static_rgb([128, 64, 255], None, None).unwrap();
+
+        let mut enable_writes = aw2013
+            .i2c
+            .writes()
+            .iter()
+            .filter(|(reg, _)| *reg == REG_LED_ENABLE);
+
+        assert_eq!(enable_writes.clone().count(), 2);
+        assert!(enable_writes.all(|(_, value)| *value == 0x7));
+
+        assert_eq!(aw2013.i2c.register(REG_LED_PWM_BASE + 1), 64);
+    }
+
+    #[test]
+    fn static_rgb_fades_start_together() {
+        let mut aw2013 = driver();
+        aw2013
+            .set_static_rgb(
+                [0x10, 0x20, 0x30],
+                Some(FadeTime::Ms520),
+                Some(FadeTime::Ms2080),
+            )
+            .unwrap();
+
+        let writes = aw2013.i2c.writes();
+        let transactions = aw2013.i2c.write_transactions();
+
+        for led in Led::ALL {
+            let offset = (led as u8) * 3;
+            let fade_in = writes
+                .iter()
+                .find(|(reg, _)| *reg == REG_TIMING_0_BASE + offset);
+            let fade_out = writes
+                .iter()
+                .find(|(reg, _)| *reg == REG_TIMING_1_BASE + offset);
+
+            assert_eq!(
+                fade_in.map(|(_, value)| value >> 4),
+                Some(FadeTime::Ms520 as u8)
+            );
+            assert_eq!(
+                fade_out.map(|(_, value)| value >> 4),
+                Some(FadeTime::Ms2080 as u8)
+            );
+        }
+
+        let mut pwm_transactions = writes
+            .iter()
+            .zip(transactions)
+            .filter(|((reg, _), _)| (REG_LED_PWM_BASE..REG_LED_PWM_BASE + 3).contains(reg))
+            .map(|(_, transaction)| *transaction);
+        let first_pwm_transaction = pwm_transactions.next();
+
+        assert!(first_pwm_transaction.is_some());
+        assert_eq!(pwm_transactions.clone().count(), 2);
+        assert!(pwm_transactions.all(|transaction| Some(transaction) == first_pwm_transaction));
+
+        let enable_writes = writes
+            .iter()
+            .filter(|(reg, _)| *reg == REG_LED_ENABLE)
+            .count();
+
+        assert_eq!(enable_writes, 1);
+        assert_eq!(writes.last(), Some(&(REG_LED_ENABLE, 0x7)));
+    }
+
+    #[test]
+    fn stop_breathing_holds_pwm_before_leaving_breathe_mode() {
+        let mut aw2013 = driver();
+        aw2013
+            .set_breathing(Led::Led2, 0xff, &Timing::default())
+            .unwrap();
+        aw2013.i2c.clear_writes();
+
+        aw2013.stop_breathing(Led::Led2, 0x40).unwrap();
+
+        let writes = aw2013.i2c.writes();
+        let pwm_write = writes
+            .iter()
+            .position(|write| *write == (REG_LED_PWM_BASE + 2, 0x40));
+        let mode_write = writes
+            .iter()
+            .position(|(reg, _)| *reg == REG_LED_MODE_BASE + 2);
+
+        assert!(pwm_write.is_some());
+        assert!(pwm_write < mode_write);
+        assert_eq!(
+            aw2013.i2c.register(REG_LED_MODE_BASE + 2) & LED_BREATHE_MODE_MASK,
+            0
+        );
+        assert_eq!(aw2013.enabled_leds().unwrap(), [false, false, true]);
+    }
+
+    #[test]
+    fn restore_brings_back_breathing_timing() {
+        let timing = Timing {
+            rise: 5,
+            hold: 2,
+            ..Timing::default()
+        };
+        let mut aw2013 = driver();
+        aw2013.set_breathing(Led::Led0, 0xff, &timing).unwrap();
+        let state = aw2013.snapshot().unwrap();
+
+        aw2013
+            .set_breathing(Led::Led0, 0x80, &Timing::default())
+            .unwrap();
+        aw2013.restore(&state).unwrap();
+
+        assert_eq!(aw2013.snapshot().unwrap(), state);
+        assert_eq!(aw2013.i2c.register(REG_TIMING_0_BASE), 0x52);
+    }
+
+    #[test]
+    fn self_test_leaves_leds_dark_with_previous_modes() {
+        let mut aw2013 = driver();
+        aw2013.i2c.set_register(REG_LED_MODE_BASE + 1, 0x23);
+        let mut steps = 0;
+
+        aw2013.self_test(&mut || steps += 1).unwrap();
+
+        assert_eq!(steps, 3);
+        assert_eq!(aw2013.enabled_leds().unwrap(), [false; 3]);
+        assert_eq!(aw2013.get_brightness_rgb().unwrap(), [0; 3]);
+        assert_eq!(aw2013.i2c.register(REG_LED_MODE_BASE), 0);
+        assert_eq!(aw2013.i2c.register(REG_LED_MODE_BASE + 1), 0x23);
+    }
+
+    #[test]
+    fn restore_keeps_snapshot_off_channels_dark() {
+        let mut aw2013 = driver();
+        aw2013.set_static(Led::Led1, 0x20, None, None).unwrap();
+        aw2013.i2c.set_register(REG_LED_PWM_BASE, 0xff);
+        let state = aw2013.snapshot().unwrap();
+
+        aw2013.set_static(Led::Led0, 0x10, None, None).unwrap();
+        aw2013.i2c.clear_writes();
+        aw2013.restore(&state).unwrap();
+
+        let mut enable_value = 0b011;
+
+        for (reg, value) in aw2013.i2c.writes() {
+            if *reg == REG_LED_ENABLE {
+                enable_value = *value;
+                continue;
+            }
+
+            for led in Led::ALL {
+                let bit = 1 << (led as u8);
+                let channel_registers = [
+                    REG_LED_PWM_BASE + (led as u8),
+                    REG_LED_MODE_BASE + (led as u8),
+                ];
+
+                if channel_registers.contains(reg) && state.led_enable & bit == 0 {
+                    assert_eq!(enable_value & bit, 0, "{:#04x} written while enabled", reg);
+                }
+            }
+        }
+
+        assert_eq!(aw2013.snapshot().unwrap(), state);
+    }
+
+    #[test]
+    fn batch_coalesces_runs_and_enables_last() {
+        let mut aw2013 = driver();
+        let mut batch = aw2013.begin_batch();
+        batch
+            .set_enabled_leds(0x7)
+            .set_pwm(Led::Led0, 0x01)
+            .set_pwm(Led::Led1, 0x02)
+            .write_register(REG_TIMING_0_BASE + 3, 0x33)
+            .set_pwm(Led::Led0, 0x03);
+
+        for led in Led::ALL {
+            batch.set_mode(led, Current::Five as u8);
+        }
+
+        batch.flush().unwrap();
+
+        assert_eq!(
+            aw2013.i2c.writes(),
+            [
+                (REG_LED_MODE_BASE, 0x01),
+                (REG_LED_MODE_BASE + 1, 0x01),
+                (REG_LED_MODE_BASE + 2, 0x01),
+                (REG_LED_PWM_BASE, 0x03),
+                (REG_LED_PWM_BASE + 1, 0x02),
+                (REG_TIMING_0_BASE + 3, 0x33),
+                (REG_LED_ENABLE, 0x7),
+            ]
+        );
+
+        let transactions = aw2013.i2c.write_transactions();
+
+        assert!(transactions[..5].iter().all(|t| *t == transactions[0]));
+        assert!(transactions[4] < transactions[5]);
+        assert!(transactions[5] < transactions[6]);
+    }
+
+    #[test]
+    fn raw_reset_clears_pwm_shadow() {
+        let mut aw2013 = driver();
+        let rgb = Rgb::new(0x10, 0x20, 0x30);
+        aw2013.set_pwm_rgb(Rgb::new(0, 0, 0)).unwrap();
+        aw2013.set_pwm_rgb(rgb).unwrap();
+
+        aw2013.write_raw(REG_RESET, LED_RESET_MASK).unwrap();
+
+        assert!(aw2013.set_rgb_if_changed(rgb).unwrap());
+        assert_eq!(aw2013.get_brightness_rgb().unwrap(), [0x10, 0x20, 0x30]);
+
+        let mut batch = aw2013.begin_batch();
+        batch.write_register(REG_RESET, LED_RESET_MASK);
+        batch.flush().unwrap();
+
+        assert!(!aw2013.set_rgb_if_changed(Rgb::new(0, 0, 0)).unwrap());
+        assert!(aw2013.set_rgb_if_changed(rgb).unwrap());
+    }
+}
@@ -0,0 +1,176 @@
+//! Recording I2C bus for host-side tests, available with the `test-util` feature.
+//!
+//! [`MockI2c`] emulates the register file of an AW2013 closely enough to drive [`crate::Aw2013`]
+//! against it and records every register write, so tests can assert on the exact sequence the
+//! driver emits.
+//!
+//! ```
+//! use aw2013::mock::MockI2c;
+//! use aw2013::registers::{REG_LED_ENABLE, REG_LED_PWM_BASE};
+//! use aw2013::{Aw2013, Current, Led};
+//!
+//! let mut aw2013 = Aw2013::from_default_address(MockI2c::<16>::new(), [Current::Five; 3]);
+//! aw2013.set_static(Led::Led1, 0x80, None, None).unwrap();
+//!
+//! let i2c = aw2013.release();
+//! assert_eq!(i2c.register(REG_LED_PWM_BASE + 1), 0x80);
+//! assert_eq!(i2c.register(REG_LED_ENABLE), 0b010);
+//! ```
+
+use core::convert::Infallible;
+
+use embedded_hal::i2c::{ErrorType, I2c, Operation, SevenBitAddress};
+
+use crate::registers::{CHIP_ID, LED_RESET_MASK, REG_RESET};
+use crate::REGISTER_COUNT;
+
+/// In-memory AW2013 which records register writes.
+///
+/// The mock answers on every address and behaves like the chip where the driver relies on it:
+///
+/// - The first written byte of a transaction sets the register pointer, which auto-increments with
+///   every further byte written or read.
+/// - Reading the reset register returns the chip ID `0x33`.
+/// - Writing `0x55` to the reset register clears all other registers.
+///
+/// Up to `N` writes are kept in the log. Further writes still update the register file, but are
+/// not logged and mark the log as overflowed.
+#[derive(Clone, Debug)]
+pub struct MockI2c<const N: usize = 64> {
+    registers: [u8; REGISTER_COUNT],
+    pointer: u8,
+    writes: [(u8, u8); N],
+    write_transactions: [usize; N],
+    write_count: usize,
+    transaction: usize,
+    overflowed: bool,
+}
+
+impl<const N: usize> MockI2c<N> {
+    /// Create a new mock with all registers at zero and an empty write log.
+    pub const fn new() -> Self {
+        Self {
+            registers: [0; REGISTER_COUNT],
+            pointer: 0,
+            writes: [(0, 0); N],
+            write_transactions: [0; N],
+            write_count: 0,
+            transaction: 0,
+            overflowed: false,
+        }
+    }
+
+    /// Current value of a register.
+    ///
+    /// Registers outside of the LED register map always read as zero.
+    pub fn register(&self, register: u8) -> u8 {
+        match register {
+            REG_RESET => CHIP_ID,
+            _ => self.registers.get(register as usize).copied().unwrap_or(0),
+        }
+    }
+
+    /// Preset the value of a register without logging it, e.g. to emulate the status register.
+    ///
+    /// Writes outside of the LED register map are ignored.
+    pub fn set_register(&mut self, register: u8, value: u8) {
+        if let Some(slot) = self.registers.get_mut(register as usize) {
+            *slot = value;
+        }
+    }
+
+    /// All logged writes as `(register, value)` pairs in the order they were received.
+    pub fn writes(&self) -> &[(u8, u8)] {
+        &self.writes[..self.write_count]
+    }
+
+    /// Transaction number of every logged write, in the same order as [`MockI2c::writes`].
+    ///
+    /// Writes sharing a number were sent as a single burst.
+    pub fn write_transactions(&self) -> &[usize] {
+        &self.write_transactions[..self.write_count]
+    }
+
+    /// Whether more than `N` writes were received since the log was last cleared.
+    pub fn is_overflowed(&self) -> bool {
+        self.overflowed
+    }
+
+    /// Clear the write log while keeping the register values.
+    pub fn clear_writes(&mut self) {
+        self.write_count = 0;
+        self.overflowed = false;
+    }
+
+    fn write_byte(&mut self, value: u8) {
+        let register = self.pointer;
+        self.pointer = self.pointer.wrapping_add(1);
+
+        if self.write_count < N {
+            self.writes[self.write_count] = (register, value);
+            self.write_transactions[self.write_count] = self.transaction;
+            self.write_count += 1;
+        } else {
+            self.overflowed = true;
+        }
+
+        if register == REG_RESET {
+            if value == LED_RESET_MASK {
+                self.registers = [0; REGISTER_COUNT];
+            }
+
+            return;
+        }
+
+        self.set_register(register, value);
+    }
+
+    fn read_byte(&mut self) -> u8 {
+        let value = self.register(self.pointer);
+        self.pointer = self.pointer.wrapping_add(1);
+        value
+    }
+}
+
+impl<const N: usize> Default for MockI2c<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> ErrorType for MockI2c<N> {
+    type Error = Infallible;
+}
+
+impl<const N: usize> I2c<SevenBitAddress> for MockI2c<N> {
+    fn transaction(
+        &mut self,
+        _address: SevenBitAddress,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        let mut pointer_set = false;
+        self.transaction += 1;
+
+        for operation in operations {
+            match operation {
+                Operation::Write(bytes) => {
+                    for &byte in bytes.iter() {
+                        if pointer_set {
+                            self.write_byte(byte);
+                        } else {
+                            self.pointer = byte;
+                            pointer_set = true;
+                        }
+                    }
+                }
+                Operation::Read(buffer) => {
+                    for byte in buffer.iter_mut() {
+                        *byte = self.read_byte();
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,58 @@
+//! Register addresses and bits of the controller.
+//!
+//! These are meant for use with [`crate::Aw2013::write_raw`] and [`crate::Aw2013::read_raw`], when
+//! accessing features which are not modelled by the driver.
+
+// Register addresses
+
+/// Reset register, writing [`LED_RESET_MASK`] resets the controller and reading returns [`CHIP_ID`].
+pub const REG_RESET: u8 = 0x00;
+
+/// Global control register.
+pub const REG_GLOBAL_CONTROL: u8 = 0x01;
+
+/// Status register, with bit `n` flagging that LED `n` completed its breathing cycles.
+///
+/// Reading may clear the flags, see [`crate::Aw2013::is_breathing_done`].
+pub const REG_STATUS: u8 = 0x02;
+
+/// LED enable register, with bit `n` enabling LED `n`.
+pub const REG_LED_ENABLE: u8 = 0x30;
+
+/// Mode register of LED 0, followed by those of LED 1 and 2.
+pub const REG_LED_MODE_BASE: u8 = 0x31;
+
+/// PWM register of LED 0, followed by those of LED 1 and 2.
+pub const REG_LED_PWM_BASE: u8 = 0x34;
+
+/// First timing register of LED 0, repeating every three registers for LED 1 and 2.
+pub const REG_TIMING_0_BASE: u8 = 0x37;
+
+/// Second timing register of LED 0, repeating every three registers for LED 1 and 2.
+pub const REG_TIMING_1_BASE: u8 = 0x38;
+
+/// Third timing register of LED 0, repeating every three registers for LED 1 and 2.
+pub const REG_TIMING_2_BASE: u8 = 0x39;
+
+// Register bits
+
+/// Chip enable bit in the global control register.
+pub const LED_MODULE_ENABLE_MASK: u8 = 0x01;
+
+/// Max current bits in the mode registers.
+pub const LED_CURRENT_MASK: u8 = 0x03;
+
+/// Fade-out bit in the mode registers.
+pub const LED_FADE_OUT_MASK: u8 = 0x40;
+
+/// Fade-in bit in the mode registers.
+pub const LED_FADE_IN_MASK: u8 = 0x20;
+
+/// Breathing mode bit in the mode registers.
+pub const LED_BREATHE_MODE_MASK: u8 = 0x10;
+
+/// Value to write to the reset register to reset the controller.
+pub const LED_RESET_MASK: u8 = 0x55;
+
+/// Value read back from the reset register.
+pub const CHIP_ID: u8 = 0x33;
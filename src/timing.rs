@@ -0,0 +1,382 @@
+//! Breathing timing configuration and its mapping from durations.
+
+use core::fmt;
+
+// Duration of a single timing step in milliseconds
+const STEP_MS: u32 = 130;
+
+/// Timing configuration for breathing effects.
+///
+/// If a supplied value is set too high, it is automatically clamped to the
+/// allowed range. Use [`Timing::validate`] to detect such values instead, or
+/// [`Timing::clamped`] to normalize them up front. The allowed ranges are
+/// available as [`Timing::MAX_DELAY`] and its siblings.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Timing {
+    /// Delay time before breathing cycle starts.
+    ///
+    /// - 0: 0s
+    /// - 1: 0.13s
+    /// - 2: 0.26s
+    /// - 3: 0.52s
+    /// - 4: 1.04s
+    /// - 5: 2.08s
+    /// - 6: 4.16s
+    /// - 7: 8.32s
+    /// - 8: 16.64s
+    pub delay: u8,
+
+    /// Rise period of breathing cycle.
+    ///
+    /// - 0: 0.13s
+    /// - 1: 0.26s
+    /// - 2: 0.52s
+    /// - 3: 1.04s
+    /// - 4: 2.08s
+    /// - 5: 4.16s
+    /// - 6: 8.32s
+    /// - 7: 16.64s
+    pub rise: u8,
+
+    /// Hold period of the breathing cycle.
+    ///
+    /// - 0: 0.13s
+    /// - 1: 0.26s
+    /// - 2: 0.52s
+    /// - 3: 1.04s
+    /// - 4: 2.08s
+    /// - 5: 4.16s
+    pub hold: u8,
+
+    /// Fall period of breathing cycle.
+    ///
+    /// - 0: 0.13s
+    /// - 1: 0.26s
+    /// - 2: 0.52s
+    /// - 3: 1.04s
+    /// - 4: 2.08s
+    /// - 5: 4.16s
+    /// - 6: 8.32s
+    /// - 7: 16.64s
+    pub fall: u8,
+
+    /// Off period of breathing cycle.
+    ///
+    /// - 0: 0.13s
+    /// - 1: 0.26s
+    /// - 2: 0.52s
+    /// - 3: 1.04s
+    /// - 4: 2.08s
+    /// - 5: 4.16s
+    /// - 6: 8.32s
+    /// - 7: 16.64s
+    pub off: u8,
+
+    /// Number of breathing cycles.
+    ///
+    /// Zero cycles means infinite, maximum configurable cycles is 15.
+    pub cycles: u8,
+}
+
+/// Fade time for static transitions.
+///
+/// The steps match those of [`Timing::rise`] and [`Timing::fall`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(u8)]
+pub enum FadeTime {
+    Ms130 = 0x0,
+    Ms260 = 0x1,
+    Ms520 = 0x2,
+    Ms1040 = 0x3,
+    Ms2080 = 0x4,
+    Ms4160 = 0x5,
+    Ms8320 = 0x6,
+    Ms16640 = 0x7,
+}
+
+/// A pleasant, clearly visible breathing cycle which repeats infinitely.
+///
+/// - delay: 0s
+/// - rise: 1.04s
+/// - hold: 0.26s
+/// - fall: 1.04s
+/// - off: 0.52s
+impl Default for Timing {
+    fn default() -> Self {
+        Self {
+            delay: 0,
+            rise: 3,
+            hold: 1,
+            fall: 3,
+            off: 2,
+            cycles: 0,
+        }
+    }
+}
+
+/// A [`Timing`] field which is outside its allowed range.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum TimingError {
+    Delay { provided: u8, max: u8 },
+    Rise { provided: u8, max: u8 },
+    Hold { provided: u8, max: u8 },
+    Fall { provided: u8, max: u8 },
+    Off { provided: u8, max: u8 },
+    Cycles { provided: u8, max: u8 },
+}
+
+impl fmt::Display for TimingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (field, provided, max) = match *self {
+            TimingError::Delay { provided, max } => ("delay", provided, max),
+            TimingError::Rise { provided, max } => ("rise", provided, max),
+            TimingError::Hold { provided, max } => ("hold", provided, max),
+            TimingError::Fall { provided, max } => ("fall", provided, max),
+            TimingError::Off { provided, max } => ("off", provided, max),
+            TimingError::Cycles { provided, max } => ("cycles", provided, max),
+        };
+
+        write!(
+            f,
+            "timing field `{}` is {}, but at most {} is allowed",
+            field, provided, max
+        )
+    }
+}
+
+/// Requested durations of a breathing cycle in milliseconds.
+///
+/// Use [`Timing::from_durations`] to map these to the closest timing the hardware supports.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Durations {
+    /// Delay time before breathing cycle starts.
+    pub delay_ms: u32,
+
+    /// Rise period of breathing cycle.
+    pub rise_ms: u32,
+
+    /// Hold period of breathing cycle.
+    pub hold_ms: u32,
+
+    /// Fall period of breathing cycle.
+    pub fall_ms: u32,
+
+    /// Off period of breathing cycle.
+    pub off_ms: u32,
+
+    /// Number of breathing cycles.
+    ///
+    /// Zero cycles means infinite, values above 15 are clamped.
+    pub cycles: u8,
+}
+
+/// Rounding which occurred while mapping [`Durations`] to a [`Timing`].
+///
+/// Each field holds the difference between the duration the hardware will actually use and the
+/// requested duration in milliseconds. A positive value means the hardware period is longer than
+/// requested, a negative value means it is shorter.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RoundingInfo {
+    pub delay_ms: i32,
+    pub rise_ms: i32,
+    pub hold_ms: i32,
+    pub fall_ms: i32,
+    pub off_ms: i32,
+}
+
+impl RoundingInfo {
+    /// Whether all requested durations are represented exactly.
+    pub fn is_exact(&self) -> bool {
+        *self == RoundingInfo::default()
+    }
+}
+
+impl Timing {
+    /// Maximum value of [`Timing::delay`].
+    pub const MAX_DELAY: u8 = 8;
+
+    /// Maximum value of [`Timing::rise`].
+    pub const MAX_RISE: u8 = 7;
+
+    /// Maximum value of [`Timing::hold`].
+    pub const MAX_HOLD: u8 = 5;
+
+    /// Maximum value of [`Timing::fall`].
+    pub const MAX_FALL: u8 = 7;
+
+    /// Maximum value of [`Timing::off`].
+    pub const MAX_OFF: u8 = 7;
+
+    /// Maximum value of [`Timing::cycles`].
+    pub const MAX_CYCLES: u8 = 15;
+
+    /// Return a copy with every field clamped to its allowed range.
+    ///
+    /// The result is exactly what gets written to the controller, so it will pass
+    /// [`Timing::validate`] and is not modified any further when applied.
+    pub fn clamped(self) -> Timing {
+        Timing {
+            delay: self.delay.min(Self::MAX_DELAY),
+            rise: self.rise.min(Self::MAX_RISE),
+            hold: self.hold.min(Self::MAX_HOLD),
+            fall: self.fall.min(Self::MAX_FALL),
+            off: self.off.min(Self::MAX_OFF),
+            cycles: self.cycles.min(Self::MAX_CYCLES),
+        }
+    }
+
+    /// Check that every field is within its allowed range.
+    ///
+    /// Returns an error naming the first field which is out of range.
+    pub fn validate(&self) -> Result<(), TimingError> {
+        if self.delay > Self::MAX_DELAY {
+            return Err(TimingError::Delay {
+                provided: self.delay,
+                max: Self::MAX_DELAY,
+            });
+        }
+
+        if self.rise > Self::MAX_RISE {
+            return Err(TimingError::Rise {
+                provided: self.rise,
+                max: Self::MAX_RISE,
+            });
+        }
+
+        if self.hold > Self::MAX_HOLD {
+            return Err(TimingError::Hold {
+                provided: self.hold,
+                max: Self::MAX_HOLD,
+            });
+        }
+
+        if self.fall > Self::MAX_FALL {
+            return Err(TimingError::Fall {
+                provided: self.fall,
+                max: Self::MAX_FALL,
+            });
+        }
+
+        if self.off > Self::MAX_OFF {
+            return Err(TimingError::Off {
+                provided: self.off,
+                max: Self::MAX_OFF,
+            });
+        }
+
+        if self.cycles > Self::MAX_CYCLES {
+            return Err(TimingError::Cycles {
+                provided: self.cycles,
+                max: Self::MAX_CYCLES,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Map durations to the closest timing supported by the hardware.
+    ///
+    /// Returns the timing together with the rounding error of every field.
+    pub fn from_durations(durations: &Durations) -> (Timing, RoundingInfo) {
+        let delay = closest_index(durations.delay_ms, Self::MAX_DELAY, delay_ms);
+        let rise = closest_index(durations.rise_ms, Self::MAX_RISE, period_ms);
+        let hold = closest_index(durations.hold_ms, Self::MAX_HOLD, period_ms);
+        let fall = closest_index(durations.fall_ms, Self::MAX_FALL, period_ms);
+        let off = closest_index(durations.off_ms, Self::MAX_OFF, period_ms);
+
+        (
+            Timing {
+                delay,
+                rise,
+                hold,
+                fall,
+                off,
+                cycles: durations.cycles.min(Self::MAX_CYCLES),
+            },
+            RoundingInfo {
+                delay_ms: difference(delay_ms(delay), durations.delay_ms),
+                rise_ms: difference(period_ms(rise), durations.rise_ms),
+                hold_ms: difference(period_ms(hold), durations.hold_ms),
+                fall_ms: difference(period_ms(fall), durations.fall_ms),
+                off_ms: difference(period_ms(off), durations.off_ms),
+            },
+        )
+    }
+
+    /// Duration of a single breathing cycle in milliseconds, made up of rise, hold, fall and off.
+    ///
+    /// Fields which are set too high are clamped, just like when applying the timing.
+    pub fn cycle_duration_ms(&self) -> u32 {
+        let timing = self.clamped();
+
+        period_ms(timing.rise)
+            + period_ms(timing.hold)
+            + period_ms(timing.fall)
+            + period_ms(timing.off)
+    }
+
+    /// Total duration of the breathing effect in milliseconds, including the initial delay.
+    ///
+    /// Returns `None` for infinite cycles.
+    pub fn total_duration_ms(&self) -> Option<u32> {
+        if self.cycles == 0 {
+            return None;
+        }
+
+        let timing = self.clamped();
+
+        Some(delay_ms(timing.delay) + timing.cycles as u32 * timing.cycle_duration_ms())
+    }
+
+    /// Register values for the three timing registers, with every field clamped.
+    pub(crate) fn to_registers(self) -> [u8; 3] {
+        let timing = self.clamped();
+
+        [
+            timing.rise << 4 | timing.hold,
+            timing.fall << 4 | timing.off,
+            timing.delay << 4 | timing.cycles,
+        ]
+    }
+
+    /// Whether this timing represents the given durations exactly.
+    pub fn is_exact_for(&self, durations: &Durations) -> bool {
+        let (timing, rounding) = Timing::from_durations(durations);
+
+        rounding.is_exact() && timing == self.clamped()
+    }
+}
+
+fn delay_ms(index: u8) -> u32 {
+    if index == 0 {
+        0
+    } else {
+        period_ms(index - 1)
+    }
+}
+
+fn period_ms(index: u8) -> u32 {
+    STEP_MS << index
+}
+
+fn closest_index(target_ms: u32, max: u8, duration_ms: fn(u8) -> u32) -> u8 {
+    let mut closest = 0;
+
+    for index in 1..=max {
+        if duration_ms(index).abs_diff(target_ms) < duration_ms(closest).abs_diff(target_ms) {
+            closest = index;
+        }
+    }
+
+    closest
+}
+
+fn difference(actual_ms: u32, requested_ms: u32) -> i32 {
+    actual_ms as i32 - requested_ms.min(i32::MAX as u32) as i32
+}